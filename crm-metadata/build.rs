@@ -10,6 +10,10 @@ fn main() -> Result<()> {
     builder
         .out_dir(path)
         .with_type_attributes(&["MaterializeRequest"], &[r#"#[derive(Eq, Hash)]"#])
+        .with_type_attributes(
+            &["BatchGetItem.item"],
+            &[r#"#[allow(clippy::large_enum_variant)]"#],
+        )
         .compile(
             &[
                 "../protos/metadata/messages.proto",
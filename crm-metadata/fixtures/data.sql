@@ -0,0 +1,74 @@
+INSERT INTO publishers (id, name, avatar) VALUES (1, 'Alice Chen', 'https://placehold.co/400x400');
+INSERT INTO publishers (id, name, avatar) VALUES (2, 'Bob Smith', 'https://placehold.co/400x400');
+INSERT INTO publishers (id, name, avatar) VALUES (3, 'Carol Jones', 'https://placehold.co/400x400');
+INSERT INTO publishers (id, name, avatar) VALUES (4, 'Dave Brown', 'https://placehold.co/400x400');
+INSERT INTO publishers (id, name, avatar) VALUES (5, 'Eve Miller', 'https://placehold.co/400x400');
+INSERT INTO publishers (id, name, avatar) VALUES (6, 'Frank Wilson', 'https://placehold.co/400x400');
+INSERT INTO publishers (id, name, avatar) VALUES (7, 'Grace Lee', 'https://placehold.co/400x400');
+INSERT INTO publishers (id, name, avatar) VALUES (8, 'Heidi Clark', 'https://placehold.co/400x400');
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (1, 'Sit Lorem Sed', 'Elit aliqua amet sit eiusmod tempor.', 'https://acme.org/contents/1', 'https://placehold.co/1600x900', 'short', '2024-04-07 10:00:00+00', 620176, 130832, 1847);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (1, 1, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (1, 5, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (1, 2, 2);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (2, 'Magna Ut Elit', 'Labore sed lorem consectetur tempor adipiscing.', 'https://acme.org/contents/2', 'https://placehold.co/1600x900', 'movie', '2024-02-11 10:00:00+00', 292369, 22728, 27985);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (2, 7, 0);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (3, 'Sit Tempor Magna', 'Sed ipsum labore sit dolore adipiscing.', 'https://acme.org/contents/3', 'https://placehold.co/1600x900', 'short', '2024-06-28 10:00:00+00', 579856, 85000, 36452);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (3, 4, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (3, 6, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (3, 1, 2);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (4, 'Ipsum Elit Do', 'Dolor elit sit incididunt amet magna.', 'https://acme.org/contents/4', 'https://placehold.co/1600x900', 'movie', '2024-12-09 10:00:00+00', 171555, 31753, 3594);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (4, 2, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (4, 5, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (4, 6, 2);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (5, 'Consectetur Magna Elit', 'Consectetur labore incididunt sed magna eiusmod.', 'https://acme.org/contents/5', 'https://placehold.co/1600x900', 'vlog', '2024-04-02 10:00:00+00', 718870, 116558, 55231);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (5, 6, 0);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (6, 'Incididunt Sed Dolor', 'Adipiscing eiusmod aliqua et dolore magna.', 'https://acme.org/contents/6', 'https://placehold.co/1600x900', 'ai_generated', '2024-09-18 10:00:00+00', 150811, 19973, 3719);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (6, 7, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (6, 5, 1);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (7, 'Incididunt Tempor Elit', 'Amet dolore et dolor incididunt lorem.', 'https://acme.org/contents/7', 'https://placehold.co/1600x900', 'short', '2024-10-14 10:00:00+00', 161265, 50592, 12773);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (7, 7, 0);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (8, 'Incididunt Labore Dolore', 'Sed magna lorem sit eiusmod aliqua.', 'https://acme.org/contents/8', 'https://placehold.co/1600x900', 'movie', '2024-03-14 10:00:00+00', 806934, 258606, 9001);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (8, 1, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (8, 6, 1);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (9, 'Sed Dolore Consectetur', 'Dolore sit do adipiscing dolor consectetur.', 'https://acme.org/contents/9', 'https://placehold.co/1600x900', 'vlog', '2024-10-01 10:00:00+00', 566579, 270116, 52263);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (9, 8, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (9, 1, 1);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (10, 'Sit Tempor Do', 'Elit ipsum aliqua dolor magna tempor.', 'https://acme.org/contents/10', 'https://placehold.co/1600x900', 'ai_generated', '2024-03-25 10:00:00+00', 856662, 29645, 65157);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (10, 8, 0);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (11, 'Magna Consectetur Sed', 'Dolore ut adipiscing aliqua tempor amet.', 'https://acme.org/contents/11', 'https://placehold.co/1600x900', 'ai_generated', '2024-08-17 10:00:00+00', 705314, 229184, 30899);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (11, 4, 0);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (12, 'Elit Dolor Eiusmod', 'Lorem magna elit dolore aliqua ipsum.', 'https://acme.org/contents/12', 'https://placehold.co/1600x900', 'short', '2024-02-11 10:00:00+00', 241062, 8123, 757);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (12, 4, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (12, 3, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (12, 6, 2);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (13, 'Et Adipiscing Amet', 'Aliqua et elit magna incididunt adipiscing.', 'https://acme.org/contents/13', 'https://placehold.co/1600x900', 'vlog', '2024-07-14 10:00:00+00', 99907, 4842, 4306);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (13, 1, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (13, 6, 1);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (14, 'Sit Ipsum Incididunt', 'Eiusmod sit elit adipiscing magna sed.', 'https://acme.org/contents/14', 'https://placehold.co/1600x900', 'ai_generated', '2024-02-08 10:00:00+00', 147991, 31217, 4122);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (14, 2, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (14, 1, 1);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (15, 'Magna Lorem Dolor', 'Elit consectetur ut et aliqua sit.', 'https://acme.org/contents/15', 'https://placehold.co/1600x900', 'ai_generated', '2024-05-13 10:00:00+00', 947279, 27772, 35899);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (15, 5, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (15, 4, 1);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (16, 'Magna Et Amet', 'Adipiscing do aliqua ipsum magna tempor.', 'https://acme.org/contents/16', 'https://placehold.co/1600x900', 'short', '2024-09-16 10:00:00+00', 785309, 123138, 3937);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (16, 3, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (16, 1, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (16, 5, 2);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (17, 'Dolor Consectetur Aliqua', 'Dolor elit incididunt sit labore do.', 'https://acme.org/contents/17', 'https://placehold.co/1600x900', 'vlog', '2024-11-14 10:00:00+00', 608040, 180745, 37660);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (17, 6, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (17, 3, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (17, 2, 2);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (18, 'Eiusmod Elit Sed', 'Incididunt amet do labore consectetur aliqua.', 'https://acme.org/contents/18', 'https://placehold.co/1600x900', 'short', '2024-02-04 10:00:00+00', 10767, 2467, 1075);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (18, 4, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (18, 5, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (18, 3, 2);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (19, 'Amet Tempor Dolor', 'Elit tempor do consectetur aliqua ut.', 'https://acme.org/contents/19', 'https://placehold.co/1600x900', 'movie', '2024-01-17 10:00:00+00', 642390, 316008, 51847);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (19, 5, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (19, 6, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (19, 1, 2);
+INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes) VALUES (20, 'Amet Sed Sit', 'Sit magna amet sed dolore do.', 'https://acme.org/contents/20', 'https://placehold.co/1600x900', 'vlog', '2024-05-28 10:00:00+00', 753470, 129175, 51800);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (20, 8, 0);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (20, 3, 1);
+INSERT INTO content_publishers (content_id, publisher_id, position) VALUES (20, 1, 2);
+SELECT setval(pg_get_serial_sequence('publishers', 'id'), (SELECT max(id) FROM publishers));
+SELECT setval(pg_get_serial_sequence('contents', 'id'), (SELECT max(id) FROM contents));
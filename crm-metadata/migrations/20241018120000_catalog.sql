@@ -0,0 +1,31 @@
+-- Add migration script here
+CREATE TYPE content_type AS ENUM ('unspecified', 'short', 'vlog', 'movie', 'ai_generated');
+
+CREATE TABLE publishers(
+    id bigint GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    name varchar(64) NOT NULL,
+    avatar varchar(256) NOT NULL DEFAULT ''
+);
+
+CREATE TABLE contents(
+    id bigint GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    name varchar(256) NOT NULL,
+    description text NOT NULL DEFAULT '',
+    url varchar(256) NOT NULL,
+    image varchar(256) NOT NULL DEFAULT '',
+    type content_type NOT NULL DEFAULT 'unspecified',
+    created_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    views bigint NOT NULL DEFAULT 0,
+    likes bigint NOT NULL DEFAULT 0,
+    dislikes bigint NOT NULL DEFAULT 0
+);
+
+-- publishers of a content, in the order they are listed
+CREATE TABLE content_publishers(
+    content_id bigint NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    publisher_id bigint NOT NULL REFERENCES publishers(id) ON DELETE CASCADE,
+    position int NOT NULL DEFAULT 0,
+    PRIMARY KEY (content_id, publisher_id)
+);
+
+CREATE INDEX content_publishers_publisher_id_idx ON content_publishers(publisher_id);
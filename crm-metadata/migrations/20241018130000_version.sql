@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE contents ADD COLUMN version bigint NOT NULL DEFAULT 1;
+
+ALTER TABLE publishers ADD COLUMN version bigint NOT NULL DEFAULT 1;
@@ -0,0 +1,15 @@
+-- Add migration script here
+ALTER TABLE contents
+    ADD COLUMN search tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', name), 'A') || setweight(to_tsvector('english', description), 'B')
+    ) STORED;
+
+CREATE INDEX contents_search_idx ON contents USING GIN(search);
+
+CREATE INDEX contents_type_idx ON contents(type);
+
+CREATE INDEX contents_created_at_idx ON contents(created_at);
+
+CREATE INDEX contents_views_idx ON contents(views);
+
+CREATE INDEX contents_likes_idx ON contents(likes);
@@ -0,0 +1,9 @@
+-- Add migration script here
+-- the name and description of a content in other locales than the default one
+CREATE TABLE content_translations(
+    content_id bigint NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    locale varchar(16) NOT NULL,
+    name varchar(256) NOT NULL,
+    description text NOT NULL DEFAULT '',
+    PRIMARY KEY (content_id, locale)
+);
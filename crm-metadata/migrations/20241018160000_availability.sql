@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TYPE content_status AS ENUM ('unspecified', 'draft', 'published', 'archived');
+
+ALTER TABLE contents
+    ADD COLUMN status content_status NOT NULL DEFAULT 'published',
+    ADD COLUMN available_from timestamptz,
+    ADD COLUMN available_until timestamptz;
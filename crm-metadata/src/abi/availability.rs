@@ -0,0 +1,220 @@
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+use tonic::{Response, Status};
+use tracing::info;
+
+use crate::{
+    pb::{Content, ContentStatus, MaterializeRequest, SetAvailabilityRequest},
+    MetadataService, ServiceResult,
+};
+
+use super::{
+    catalog::{internal, read_only, status_name},
+    content::version_conflict,
+    ts_to_utc,
+};
+
+impl Content {
+    /// whether the content is published, and the time is in its availability window
+    pub fn is_available(&self, now: DateTime<Utc>) -> bool {
+        let utc = |ts: &Option<Timestamp>| ts.as_ref().and_then(ts_to_utc);
+        self.status() == ContentStatus::Published
+            && utc(&self.available_from).is_none_or(|from| from <= now)
+            && utc(&self.available_until).is_none_or(|until| now < until)
+    }
+}
+
+impl MetadataService {
+    pub(super) async fn materialize_one(&self, req: MaterializeRequest) -> Result<Content, Status> {
+        let content = self.cached_content(req.id, &req.locale).await?;
+        if req.only_available && !content.is_available(Utc::now()) {
+            return Err(Status::failed_precondition(format!(
+                "Content {} is not available",
+                req.id
+            )));
+        }
+        Ok(content)
+    }
+
+    pub async fn set_availability(&self, req: SetAvailabilityRequest) -> ServiceResult<Content> {
+        let (from, until) = validate_availability(&req).map_err(Status::invalid_argument)?;
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+
+        let ret = sqlx::query(
+            "UPDATE contents SET status = $2::content_status, available_from = $3, available_until = $4, version = version + 1 WHERE id = $1 AND version = $5",
+        )
+        .bind(req.content_id as i64)
+        .bind(status_name(req.status()))
+        .bind(from)
+        .bind(until)
+        .bind(req.version as i64)
+        .execute(pool)
+        .await
+        .map_err(internal)?;
+        if ret.rows_affected() == 0 {
+            return Err(
+                version_conflict(pool, "contents", "Content", req.content_id, req.version).await,
+            );
+        }
+
+        info!(
+            "Content {} is {:?} from {:?} until {:?}",
+            req.content_id,
+            req.status(),
+            from,
+            until
+        );
+        self.cache.invalidate([req.content_id]);
+        let content = self.cached_content(req.content_id, "").await?;
+        Ok(Response::new(content))
+    }
+}
+
+type Window = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+fn validate_availability(req: &SetAvailabilityRequest) -> Result<Window, String> {
+    match ContentStatus::try_from(req.status) {
+        Ok(ContentStatus::Unspecified) => return Err("Content status is required".to_string()),
+        Ok(_) => {}
+        Err(_) => return Err(format!("Invalid content status: {}", req.status)),
+    }
+
+    let to_utc = |field: &str, ts: &Option<Timestamp>| match ts {
+        Some(ts) => ts_to_utc(ts)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid {}", field)),
+        None => Ok(None),
+    };
+    let from = to_utc("available_from", &req.available_from)?;
+    let until = to_utc("available_until", &req.available_until)?;
+    if let (Some(from), Some(until)) = (from, until) {
+        if from >= until {
+            return Err("available_from must be before available_until".to_string());
+        }
+    }
+    Ok((from, until))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::to_ts;
+    use anyhow::Result;
+    use chrono::Duration;
+    use futures::StreamExt;
+    use tonic::Code;
+
+    #[test]
+    fn is_available_should_check_status_and_window() {
+        let now = Utc::now();
+        let content = Content {
+            status: ContentStatus::Published as i32,
+            ..Default::default()
+        };
+        assert!(content.is_available(now));
+
+        let window = |from: i64, until: i64| Content {
+            available_from: Some(to_ts(now + Duration::days(from))),
+            available_until: Some(to_ts(now + Duration::days(until))),
+            ..content.clone()
+        };
+        assert!(window(-1, 1).is_available(now));
+        assert!(!window(1, 2).is_available(now));
+        assert!(!window(-2, -1).is_available(now));
+
+        for status in [ContentStatus::Draft, ContentStatus::Archived] {
+            let content = Content {
+                status: status as i32,
+                ..content.clone()
+            };
+            assert!(!content.is_available(now));
+        }
+    }
+
+    #[tokio::test]
+    async fn materialize_should_skip_unavailable_contents() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+        let req = SetAvailabilityRequest {
+            content_id: 1,
+            status: ContentStatus::Archived as i32,
+            version: 1,
+            ..Default::default()
+        };
+        let content = service.set_availability(req).await?.into_inner();
+        assert_eq!(content.status(), ContentStatus::Archived);
+        let req = SetAvailabilityRequest {
+            content_id: 2,
+            status: ContentStatus::Published as i32,
+            available_from: Some(to_ts(Utc::now() + Duration::days(1))),
+            available_until: None,
+            version: 1,
+        };
+        service.set_availability(req).await?;
+
+        let only_available = |id| MaterializeRequest {
+            only_available: true,
+            ..MaterializeRequest::new(id, "")
+        };
+        let stream = tokio_stream::iter(vec![
+            Ok(only_available(1)),
+            Ok(only_available(2)),
+            Ok(only_available(3)),
+            Ok(MaterializeRequest::new(1, "")),
+        ]);
+        let ret: Vec<_> = service
+            .materialize(stream)
+            .await?
+            .into_inner()
+            .collect()
+            .await;
+        let e = ret[0].as_ref().unwrap_err();
+        assert_eq!(e.code(), Code::FailedPrecondition);
+        assert_eq!(e.message(), "Content 1 is not available");
+        assert!(ret[1].is_err());
+        assert_eq!(ret[2].as_ref().unwrap().id, 3);
+        assert_eq!(ret[3].as_ref().unwrap().id, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn set_availability_should_validate_window() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+        let now = Utc::now();
+        let req = SetAvailabilityRequest {
+            content_id: 1,
+            status: ContentStatus::Published as i32,
+            available_from: Some(to_ts(now)),
+            available_until: Some(to_ts(now - Duration::days(1))),
+            version: 1,
+        };
+        let e = service.set_availability(req.clone()).await.unwrap_err();
+        assert_eq!(e.message(), "available_from must be before available_until");
+
+        let req = SetAvailabilityRequest { status: 0, ..req };
+        let e = service.set_availability(req).await.unwrap_err();
+        assert_eq!(e.message(), "Content status is required");
+
+        let req = SetAvailabilityRequest {
+            content_id: 1000,
+            status: ContentStatus::Draft as i32,
+            ..Default::default()
+        };
+        let e = service.set_availability(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+
+        let req = SetAvailabilityRequest {
+            content_id: 1,
+            status: ContentStatus::Draft as i32,
+            version: 1,
+            ..Default::default()
+        };
+        let content = service.set_availability(req.clone()).await?.into_inner();
+        assert_eq!(content.version, 2);
+        let e = service.set_availability(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::Aborted);
+        assert_eq!(e.message(), "Content 1 is at version 2, not 1");
+
+        Ok(())
+    }
+}
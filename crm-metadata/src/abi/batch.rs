@@ -0,0 +1,124 @@
+use futures::{stream, StreamExt};
+use tonic::{Code, Response, Status};
+
+use crate::{
+    pb::{
+        batch_get_item::Item, BatchGetItem, BatchGetRequest, BatchGetResponse, MaterializeRequest,
+    },
+    MetadataService, ServiceResult,
+};
+
+/// how many contents of a batch are looked up at the same time
+const CONCURRENCY: usize = 16;
+
+impl MetadataService {
+    pub async fn batch_get(&self, req: BatchGetRequest) -> ServiceResult<BatchGetResponse> {
+        let max_batch_size = self.config.server.max_batch_size;
+        if req.ids.len() > max_batch_size {
+            return Err(Status::invalid_argument(format!(
+                "At most {} ids can be given",
+                max_batch_size
+            )));
+        }
+
+        let mut results = stream::iter(req.ids)
+            .map(|id| {
+                let req = MaterializeRequest {
+                    id,
+                    locale: req.locale.clone(),
+                    only_available: req.only_available,
+                };
+                async move { (id, self.materialize_one(req).await) }
+            })
+            .buffered(CONCURRENCY);
+
+        let mut items = Vec::new();
+        while let Some((id, ret)) = results.next().await {
+            let item = match ret {
+                Ok(content) => Item::Content(content),
+                Err(e) if matches!(e.code(), Code::NotFound | Code::FailedPrecondition) => {
+                    Item::Missing(e.message().to_string())
+                }
+                // the catalog fails, not the id
+                Err(e) => return Err(e),
+            };
+            items.push(BatchGetItem {
+                id,
+                item: Some(item),
+            });
+        }
+        Ok(Response::new(BatchGetResponse { items }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pb::{ContentStatus, SetAvailabilityRequest},
+        AppConfig, CatalogConfig,
+    };
+    use anyhow::Result;
+
+    #[tokio::test]
+    async fn batch_get_should_keep_order_and_mark_missing() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+        let req = SetAvailabilityRequest {
+            content_id: 2,
+            status: ContentStatus::Draft as i32,
+            version: 1,
+            ..Default::default()
+        };
+        service.set_availability(req).await?;
+
+        let req = BatchGetRequest {
+            ids: vec![3, 1000, 1, 2, 3],
+            only_available: true,
+            ..Default::default()
+        };
+        let items = service.batch_get(req).await?.into_inner().items;
+        let ids: Vec<_> = items.iter().map(|item| item.id).collect();
+        assert_eq!(ids, [3, 1000, 1, 2, 3]);
+
+        let item = |i: usize| items[i].item.as_ref().unwrap();
+        assert!(matches!(item(0), Item::Content(c) if c.id == 3));
+        assert_eq!(
+            item(1),
+            &Item::Missing("Content 1000 not found".to_string())
+        );
+        assert!(matches!(item(2), Item::Content(c) if c.name == "Sit Lorem Sed"));
+        assert_eq!(
+            item(3),
+            &Item::Missing("Content 2 is not available".to_string())
+        );
+        assert_eq!(item(4), item(0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn batch_get_should_limit_ids() -> Result<()> {
+        let mut config = AppConfig::load()?;
+        config.catalog = CatalogConfig::Fake { seed: 42 };
+        config.server.max_batch_size = 2;
+        let service = MetadataService::new(config).await;
+        let req = BatchGetRequest {
+            ids: vec![3, 2, 1],
+            ..Default::default()
+        };
+        let e = service.batch_get(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::InvalidArgument);
+
+        let req = BatchGetRequest {
+            ids: vec![2, 1],
+            ..Default::default()
+        };
+        let items = service.batch_get(req).await?.into_inner().items;
+        assert_eq!(
+            items[1].item,
+            Some(Item::Content(crate::pb::Content::materialize(42, 1)))
+        );
+
+        Ok(())
+    }
+}
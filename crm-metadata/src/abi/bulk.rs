@@ -0,0 +1,769 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::PgPool;
+use tonic::{Code, Response, Status};
+use tracing::info;
+
+use crate::{
+    pb::{
+        CatalogFormat, CatalogRecord, Content, ContentStatus, ExportRequest, ExportResponse,
+        ImportReport, ImportRequest, Publisher, RejectedRow,
+    },
+    MetadataService, ServiceResult,
+};
+
+use super::{
+    catalog::{
+        internal, parse_status, parse_type, read_only, status_name, type_name, with_publishers,
+        ContentRow, PublisherRow, CONTENT_COLUMNS,
+    },
+    content::{link, validate_content, validate_name, MAX_NAME_LEN},
+    locale::{normalize, validate_locale},
+    publisher::validate_publisher,
+    ts_to_utc,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PublisherRecord {
+    id: u32,
+    name: String,
+    #[serde(default)]
+    avatar: String,
+}
+
+/// a content with the ids of its publishers, in order, and its translations
+#[derive(Debug, Serialize, Deserialize)]
+struct ContentRecord<P = Vec<u32>, T = Option<Vec<TranslationRecord>>> {
+    id: u32,
+    name: String,
+    #[serde(default)]
+    description: String,
+    url: String,
+    #[serde(default)]
+    image: String,
+    r#type: String,
+    /// now for a new content if not set
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    views: u64,
+    #[serde(default)]
+    likes: u64,
+    #[serde(default)]
+    dislikes: u64,
+    #[serde(default)]
+    publisher_ids: P,
+    /// published if not set
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    available_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    available_until: Option<DateTime<Utc>>,
+    /// replace the translations of the content, they are kept if not set
+    #[serde(default)]
+    translations: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TranslationRecord {
+    locale: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// the publisher ids in a single csv field
+#[derive(Debug, Default)]
+struct JoinedIds(Vec<u32>);
+
+/// the translations as a json array in a single csv field, an empty field is no translation
+#[derive(Debug, Default)]
+struct JsonTranslations(Option<Vec<TranslationRecord>>);
+
+type CsvContentRecord = ContentRecord<JoinedIds, JsonTranslations>;
+
+/// a row of the data with its line number, or why it can't be read
+type Row<T> = (u32, Result<T, String>);
+
+impl MetadataService {
+    pub async fn import(&self, req: ImportRequest) -> ServiceResult<ImportReport> {
+        let format = req.format();
+        if format == CatalogFormat::Unspecified {
+            return Err(format_required());
+        }
+        let table = match req.record() {
+            CatalogRecord::Content => "contents",
+            CatalogRecord::Publisher => "publishers",
+            CatalogRecord::Unspecified => return Err(record_required()),
+        };
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+
+        let mut report = ImportReport::default();
+        let ret = import_rows(pool, &req, &mut report).await;
+        if report.imported > 0 {
+            // a publisher change shows in all its contents, so drop them all. The rows
+            // imported before a failing one are kept, so it's done on failure too
+            self.cache.clear();
+        }
+        ret?;
+        if report.imported > 0 {
+            reset_sequence(pool, table).await?;
+        }
+        info!(
+            "Imported {} {}, {} rejected",
+            report.imported,
+            table,
+            report.rejected.len()
+        );
+        Ok(Response::new(report))
+    }
+
+    pub async fn export(&self, req: ExportRequest) -> ServiceResult<ExportResponse> {
+        let format = req.format();
+        if format == CatalogFormat::Unspecified {
+            return Err(format_required());
+        }
+        let pool = self
+            .catalog
+            .pool()
+            .ok_or_else(|| Status::failed_precondition("The fake catalog can't be exported"))?;
+
+        let data = match req.record() {
+            CatalogRecord::Content => {
+                let sql = format!("SELECT {} FROM contents ORDER BY id", CONTENT_COLUMNS);
+                let rows: Vec<ContentRow> = sqlx::query_as(&sql)
+                    .fetch_all(pool)
+                    .await
+                    .map_err(internal)?;
+                let mut translations = translations(pool).await?;
+                let records = with_publishers(pool, rows)
+                    .await?
+                    .into_iter()
+                    .map(|content| {
+                        let translations = translations.remove(&content.id).unwrap_or_default();
+                        ContentRecord::from(content).map_lists(|ids| ids, |_| Some(translations))
+                    });
+                match format {
+                    CatalogFormat::Csv => write_rows(
+                        format,
+                        records.map(|r| r.map_lists(JoinedIds, JsonTranslations)),
+                    ),
+                    _ => write_rows(format, records),
+                }
+            }
+            CatalogRecord::Publisher => {
+                let rows: Vec<PublisherRow> =
+                    sqlx::query_as("SELECT id, name, avatar, version FROM publishers ORDER BY id")
+                        .fetch_all(pool)
+                        .await
+                        .map_err(internal)?;
+                let records = rows.into_iter().map(|row| {
+                    let publisher = Publisher::from(row);
+                    PublisherRecord {
+                        id: publisher.id,
+                        name: publisher.name,
+                        avatar: publisher.avatar,
+                    }
+                });
+                write_rows(format, records)
+            }
+            CatalogRecord::Unspecified => return Err(record_required()),
+        };
+        let data = data.map_err(Status::internal)?;
+        Ok(Response::new(ExportResponse { data }))
+    }
+}
+
+impl ImportReport {
+    /// count the row as imported, or as rejected with the reason. A failing db is given back
+    /// to fail the whole import, the rows are fine
+    fn add(&mut self, line: u32, id: u32, ret: Result<(), Status>) -> Option<Status> {
+        match ret {
+            Ok(()) => self.imported += 1,
+            Err(e) if e.code() == Code::Internal => return Some(e),
+            Err(e) => self.rejected.push(RejectedRow {
+                line,
+                id,
+                reason: e.message().to_string(),
+            }),
+        }
+        None
+    }
+}
+
+impl<P, T> ContentRecord<P, T> {
+    fn map_lists<Q, U>(
+        self,
+        ids: impl FnOnce(P) -> Q,
+        translations: impl FnOnce(T) -> U,
+    ) -> ContentRecord<Q, U> {
+        ContentRecord {
+            id: self.id,
+            name: self.name,
+            description: self.description,
+            url: self.url,
+            image: self.image,
+            r#type: self.r#type,
+            created_at: self.created_at,
+            views: self.views,
+            likes: self.likes,
+            dislikes: self.dislikes,
+            publisher_ids: ids(self.publisher_ids),
+            status: self.status,
+            available_from: self.available_from,
+            available_until: self.available_until,
+            translations: translations(self.translations),
+        }
+    }
+}
+
+impl From<Content> for ContentRecord {
+    fn from(content: Content) -> Self {
+        Self {
+            id: content.id,
+            r#type: type_name(content.r#type()),
+            created_at: content.created_at.as_ref().and_then(ts_to_utc),
+            status: Some(status_name(content.status())),
+            available_from: content.available_from.as_ref().and_then(ts_to_utc),
+            available_until: content.available_until.as_ref().and_then(ts_to_utc),
+            name: content.name,
+            description: content.description,
+            url: content.url,
+            image: content.image,
+            views: content.views,
+            likes: content.likes,
+            dislikes: content.dislikes,
+            publisher_ids: content.publishers.iter().map(|p| p.id).collect(),
+            translations: None,
+        }
+    }
+}
+
+impl Serialize for JoinedIds {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.iter().join(";"))
+    }
+}
+
+impl<'de> Deserialize<'de> for JoinedIds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let ids = s
+            .split(';')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                id.parse()
+                    .map_err(|_| serde::de::Error::custom(format!("invalid publisher id: {}", id)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(JoinedIds(ids))
+    }
+}
+
+/// upsert the rows one by one into the report, until the db fails
+async fn import_rows(
+    pool: &PgPool,
+    req: &ImportRequest,
+    report: &mut ImportReport,
+) -> Result<(), Status> {
+    let format = req.format();
+    match req.record() {
+        CatalogRecord::Content => {
+            let rows: Vec<Row<ContentRecord>> = match format {
+                CatalogFormat::Csv => read_rows::<CsvContentRecord>(format, &req.data)
+                    .map_err(Status::invalid_argument)?
+                    .into_iter()
+                    .map(|(line, row)| (line, row.map(|r| r.map_lists(|ids| ids.0, |t| t.0))))
+                    .collect(),
+                _ => read_rows(format, &req.data).map_err(Status::invalid_argument)?,
+            };
+            for (line, row) in rows {
+                let (id, ret) = match row {
+                    Ok(record) => (record.id, upsert_content(pool, record).await),
+                    Err(e) => (0, Err(Status::invalid_argument(e))),
+                };
+                if let Some(e) = report.add(line, id, ret) {
+                    return Err(e);
+                }
+            }
+        }
+        CatalogRecord::Publisher => {
+            let rows = read_rows::<PublisherRecord>(format, &req.data)
+                .map_err(Status::invalid_argument)?;
+            for (line, row) in rows {
+                let (id, ret) = match row {
+                    Ok(record) => (record.id, upsert_publisher(pool, record).await),
+                    Err(e) => (0, Err(Status::invalid_argument(e))),
+                };
+                if let Some(e) = report.add(line, id, ret) {
+                    return Err(e);
+                }
+            }
+        }
+        CatalogRecord::Unspecified => return Err(record_required()),
+    }
+    Ok(())
+}
+
+impl Serialize for JsonTranslations {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.0 {
+            Some(translations) if !translations.is_empty() => {
+                let json =
+                    serde_json::to_string(translations).map_err(serde::ser::Error::custom)?;
+                serializer.serialize_str(&json)
+            }
+            _ => serializer.serialize_str(""),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonTranslations {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.trim().is_empty() {
+            return Ok(JsonTranslations(Some(vec![])));
+        }
+        let translations = serde_json::from_str(&s)
+            .map_err(|e| serde::de::Error::custom(format!("invalid translations: {}", e)))?;
+        Ok(JsonTranslations(Some(translations)))
+    }
+}
+
+/// the translations of all the contents, keyed by the content id
+async fn translations(pool: &PgPool) -> Result<HashMap<u32, Vec<TranslationRecord>>, Status> {
+    let rows: Vec<(i64, String, String, String)> = sqlx::query_as(
+        "SELECT content_id, locale, name, description FROM content_translations ORDER BY content_id, locale",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(internal)?;
+    let mut translations: HashMap<u32, Vec<TranslationRecord>> = HashMap::new();
+    for (content_id, locale, name, description) in rows {
+        translations
+            .entry(content_id as u32)
+            .or_default()
+            .push(TranslationRecord {
+                locale,
+                name,
+                description,
+            });
+    }
+    Ok(translations)
+}
+
+async fn upsert_publisher(pool: &PgPool, record: PublisherRecord) -> Result<(), Status> {
+    if record.id == 0 {
+        return Err(Status::invalid_argument("Id is required"));
+    }
+    validate_publisher(&record.name, &record.avatar).map_err(Status::invalid_argument)?;
+
+    sqlx::query(
+        "INSERT INTO publishers (id, name, avatar) VALUES ($1, $2, $3) ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, avatar = EXCLUDED.avatar, version = publishers.version + 1",
+    )
+    .bind(record.id as i64)
+    .bind(&record.name)
+    .bind(&record.avatar)
+    .execute(pool)
+    .await
+    .map_err(internal)?;
+    Ok(())
+}
+
+/// upsert the content and replace its publishers, and its translations if given, all or nothing
+async fn upsert_content(pool: &PgPool, record: ContentRecord) -> Result<(), Status> {
+    if record.id == 0 {
+        return Err(Status::invalid_argument("Id is required"));
+    }
+    let content_type = parse_type(&record.r#type).ok_or_else(|| {
+        Status::invalid_argument(format!("Invalid content type: {}", record.r#type))
+    })?;
+    let status = match &record.status {
+        Some(name) => parse_status(name)
+            .filter(|status| *status != ContentStatus::Unspecified)
+            .ok_or_else(|| Status::invalid_argument(format!("Invalid content status: {}", name)))?,
+        None => ContentStatus::Published,
+    };
+    if let (Some(from), Some(until)) = (record.available_from, record.available_until) {
+        if from >= until {
+            return Err(Status::invalid_argument(
+                "available_from must be before available_until",
+            ));
+        }
+    }
+    validate_content(
+        &record.name,
+        &record.url,
+        &record.image,
+        content_type as i32,
+    )
+    .map_err(Status::invalid_argument)?;
+    let translations = record
+        .translations
+        .map(|translations| {
+            translations
+                .into_iter()
+                .map(|t| {
+                    let locale = normalize(&t.locale);
+                    validate_locale(&locale)?;
+                    validate_name(&t.name, MAX_NAME_LEN)?;
+                    Ok(TranslationRecord { locale, ..t })
+                })
+                .collect::<Result<Vec<_>, String>>()
+        })
+        .transpose()
+        .map_err(Status::invalid_argument)?;
+
+    let id = record.id as i64;
+    let mut tx = pool.begin().await.map_err(internal)?;
+    sqlx::query(
+        "INSERT INTO contents (id, name, description, url, image, type, created_at, views, likes, dislikes, status, available_from, available_until) VALUES ($1, $2, $3, $4, $5, $6::content_type, COALESCE($7, now()), $8, $9, $10, $11::content_status, $12, $13) ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, description = EXCLUDED.description, url = EXCLUDED.url, image = EXCLUDED.image, type = EXCLUDED.type, created_at = COALESCE($7, contents.created_at), views = EXCLUDED.views, likes = EXCLUDED.likes, dislikes = EXCLUDED.dislikes, status = EXCLUDED.status, available_from = EXCLUDED.available_from, available_until = EXCLUDED.available_until, version = contents.version + 1",
+    )
+    .bind(id)
+    .bind(&record.name)
+    .bind(&record.description)
+    .bind(&record.url)
+    .bind(&record.image)
+    .bind(type_name(content_type))
+    .bind(record.created_at)
+    .bind(record.views as i64)
+    .bind(record.likes as i64)
+    .bind(record.dislikes as i64)
+    .bind(status_name(status))
+    .bind(record.available_from)
+    .bind(record.available_until)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+
+    sqlx::query("DELETE FROM content_publishers WHERE content_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    for publisher_id in record.publisher_ids {
+        link(&mut tx, id, publisher_id).await?;
+    }
+    if let Some(translations) = translations {
+        sqlx::query("DELETE FROM content_translations WHERE content_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal)?;
+        for t in translations {
+            sqlx::query(
+                "INSERT INTO content_translations (content_id, locale, name, description) VALUES ($1, $2, $3, $4) ON CONFLICT (content_id, locale) DO UPDATE SET name = EXCLUDED.name, description = EXCLUDED.description",
+            )
+            .bind(id)
+            .bind(&t.locale)
+            .bind(&t.name)
+            .bind(&t.description)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal)?;
+        }
+    }
+    tx.commit().await.map_err(internal)?;
+    Ok(())
+}
+
+/// move the id sequence past the imported ids, so the created rows don't collide with them
+async fn reset_sequence(pool: &PgPool, table: &str) -> Result<(), Status> {
+    let sql = format!(
+        "SELECT setval(pg_get_serial_sequence('{0}', 'id'), (SELECT max(id) FROM {0}))",
+        table
+    );
+    sqlx::query(&sql).execute(pool).await.map_err(internal)?;
+    Ok(())
+}
+
+/// the records of the data, the data is rejected if the csv header can't be read
+fn read_rows<T: DeserializeOwned>(
+    format: CatalogFormat,
+    data: &[u8],
+) -> Result<Vec<Row<T>>, String> {
+    if format == CatalogFormat::Jsonl {
+        let rows = data
+            .split(|b| *b == b'\n')
+            .enumerate()
+            .filter(|(_, line)| !line.trim_ascii().is_empty())
+            .map(|(i, line)| {
+                let row = serde_json::from_slice(line).map_err(|e| e.to_string());
+                (i as u32 + 1, row)
+            })
+            .collect();
+        return Ok(rows);
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data);
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Invalid csv header: {}", e))?
+        .clone();
+    let rows = reader
+        .records()
+        .map(|record| match record {
+            Ok(record) => {
+                let line = record.position().map_or(0, |p| p.line());
+                let row = record
+                    .deserialize(Some(&headers))
+                    .map_err(|e| e.to_string());
+                (line as u32, row)
+            }
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line());
+                (line as u32, Err(e.to_string()))
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+fn write_rows<T: Serialize>(
+    format: CatalogFormat,
+    records: impl IntoIterator<Item = T>,
+) -> Result<Vec<u8>, String> {
+    let failed = |e: &dyn std::fmt::Display| format!("Failed to export: {}", e);
+    if format == CatalogFormat::Jsonl {
+        let mut data = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut data, &record).map_err(|e| failed(&e))?;
+            data.push(b'\n');
+        }
+        return Ok(data);
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for record in records {
+        writer.serialize(record).map_err(|e| failed(&e))?;
+    }
+    writer.into_inner().map_err(|e| failed(&e))
+}
+
+fn format_required() -> Status {
+    Status::invalid_argument("Format is required")
+}
+
+fn record_required() -> Status {
+    Status::invalid_argument("Record is required")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::{
+        CreatePublisherRequest, GetContentRequest, GetPublisherRequest, PutTranslationRequest,
+        Translation,
+    };
+    use anyhow::Result;
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn import_should_upsert_and_report_rejected_rows() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+
+        let data = "id,name,avatar
+1,Alice C.,https://placehold.co/400x400
+100,Zoe Park,
+101,,
+abc,Bad Id,
+";
+        let req = ImportRequest {
+            format: CatalogFormat::Csv as i32,
+            record: CatalogRecord::Publisher as i32,
+            data: data.into(),
+        };
+        let report = service.import(req).await?.into_inner();
+        assert_eq!(report.imported, 2);
+        let rejected: Vec<_> = report.rejected.iter().map(|r| (r.line, r.id)).collect();
+        assert_eq!(rejected, [(4, 101), (5, 0)]);
+        assert_eq!(report.rejected[0].reason, "Name is required");
+
+        let publisher = service
+            .get_publisher(GetPublisherRequest { id: 1 })
+            .await?
+            .into_inner();
+        assert_eq!(publisher.name, "Alice C.");
+        assert_eq!(publisher.version, 2);
+        // new publishers get ids after the imported ones
+        let req = CreatePublisherRequest {
+            name: "Created".to_string(),
+            avatar: "".to_string(),
+        };
+        assert_eq!(service.create_publisher(req).await?.into_inner().id, 101);
+
+        let data = r#"{"id":1,"name":"Imported","url":"https://acme.org/contents/1","type":"movie","publisher_ids":[100,1]}
+
+{"id":50,"name":"No Such Publisher","url":"https://acme.org/contents/50","type":"vlog","publisher_ids":[999]}
+{"id":51,"name":"No Such Type","url":"https://acme.org/contents/51","type":"podcast"}
+"#;
+        let req = ImportRequest {
+            format: CatalogFormat::Jsonl as i32,
+            record: CatalogRecord::Content as i32,
+            data: data.into(),
+        };
+        let report = service.import(req).await?.into_inner();
+        assert_eq!(report.imported, 1);
+        let rejected: Vec<_> = report
+            .rejected
+            .iter()
+            .map(|r| (r.line, r.id, r.reason.as_str()))
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                (3, 50, "Publisher 999 not found"),
+                (4, 51, "Invalid content type: podcast")
+            ]
+        );
+
+        let content = service
+            .get_content(GetContentRequest {
+                id: 1,
+                ..Default::default()
+            })
+            .await?
+            .into_inner();
+        assert_eq!(content.name, "Imported");
+        let publishers: Vec<_> = content.publishers.iter().map(|p| p.id).collect();
+        assert_eq!(publishers, [100, 1]);
+        // the rejected content isn't half imported
+        let e = service
+            .get_content(GetContentRequest {
+                id: 50,
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn export_should_round_trip() -> Result<()> {
+        let (tdb, service) = MetadataService::new_for_test().await?;
+        let req = PutTranslationRequest {
+            content_id: 1,
+            translation: Some(Translation {
+                locale: "zh-cn".to_string(),
+                name: "坐下, \"Lorem\"".to_string(),
+                description: "第一行\n第二行".to_string(),
+            }),
+        };
+        service.put_translation(req).await?;
+        let pool = tdb.get_pool().await;
+        let localized = GetContentRequest {
+            id: 1,
+            locale: "zh-cn".to_string(),
+        };
+
+        for format in [CatalogFormat::Jsonl, CatalogFormat::Csv] {
+            for record in [CatalogRecord::Publisher, CatalogRecord::Content] {
+                let req = ExportRequest {
+                    format: format as i32,
+                    record: record as i32,
+                };
+                let data = service.export(req.clone()).await?.into_inner().data;
+                if record == CatalogRecord::Content {
+                    // the translations are restored from the exported data
+                    pool.execute("DELETE FROM content_translations").await?;
+                }
+
+                let import = ImportRequest {
+                    format: format as i32,
+                    record: record as i32,
+                    data: data.clone(),
+                };
+                let report = service.import(import).await?.into_inner();
+                let expected = if record == CatalogRecord::Content {
+                    20
+                } else {
+                    8
+                };
+                assert_eq!(report.imported, expected);
+                assert!(report.rejected.is_empty());
+                if record == CatalogRecord::Content {
+                    let content = service.get_content(localized.clone()).await?.into_inner();
+                    assert_eq!(content.name, "坐下, \"Lorem\"");
+                    assert_eq!(content.description, "第一行\n第二行");
+                }
+
+                assert_eq!(service.export(req).await?.into_inner().data, data);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn import_should_drop_cached_contents_when_failing() -> Result<()> {
+        let (tdb, service) = MetadataService::new_for_test().await?;
+        let req = GetContentRequest {
+            id: 1,
+            ..Default::default()
+        };
+        service.get_content(req.clone()).await?;
+
+        // the db fails on the second row, after the first one is imported
+        tdb.get_pool()
+            .await
+            .execute(
+                "CREATE FUNCTION fail() RETURNS trigger AS $$ BEGIN RAISE EXCEPTION 'db is down'; END $$ LANGUAGE plpgsql;
+                CREATE TRIGGER fail BEFORE UPDATE ON contents FOR EACH ROW WHEN (NEW.id = 2) EXECUTE FUNCTION fail()",
+            )
+            .await?;
+        let data = r#"{"id":1,"name":"Imported","url":"https://acme.org/contents/1","type":"movie"}
+{"id":2,"name":"Failing","url":"https://acme.org/contents/2","type":"movie"}
+"#;
+        let import = ImportRequest {
+            format: CatalogFormat::Jsonl as i32,
+            record: CatalogRecord::Content as i32,
+            data: data.into(),
+        };
+        let e = service.import(import).await.unwrap_err();
+        assert_eq!(e.code(), Code::Internal);
+
+        assert_eq!(
+            service.get_content(req).await?.into_inner().name,
+            "Imported"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn import_should_need_format_and_record() -> Result<()> {
+        let service = MetadataService::new_fake(42).await?;
+        let req = ImportRequest {
+            format: CatalogFormat::Csv as i32,
+            record: CatalogRecord::Publisher as i32,
+            data: "id,name\n1,Alice\n".into(),
+        };
+        let e = service.import(req.clone()).await.unwrap_err();
+        assert_eq!(e.code(), Code::FailedPrecondition);
+
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+        let e = service
+            .import(ImportRequest {
+                format: 0,
+                ..req.clone()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(e.message(), "Format is required");
+        let e = service
+            .import(ImportRequest { record: 0, ..req })
+            .await
+            .unwrap_err();
+        assert_eq!(e.message(), "Record is required");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use hashlink::LruCache;
+use tonic::Status;
+
+use crate::{pb::Content, CacheConfig, MetadataService};
+
+use super::locale::Localized;
+
+/// the recently materialized contents in all their locales, dropped when they get old or
+/// changed
+pub struct ContentCache {
+    inner: Option<Mutex<CacheInner>>,
+    ttl: Duration,
+}
+
+struct CacheInner {
+    entries: LruCache<u32, Entry>,
+    /// bumped when a content is invalidated while it's being loaded, so the stale load is
+    /// not cached. Only the ids with a load in flight are kept
+    generations: HashMap<u32, Loading>,
+    /// bumped when the whole cache is cleared
+    epoch: u64,
+}
+
+struct Entry {
+    content: Arc<Localized>,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct Loading {
+    generation: u64,
+    loads: usize,
+}
+
+/// a content being loaded from the catalog, along with what the cache looked like when the
+/// load started, to tell if it's still fresh when the load is done
+pub struct Load<'a> {
+    cache: &'a ContentCache,
+    generation: Option<Generation>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Generation {
+    id: u32,
+    epoch: u64,
+    generation: u64,
+}
+
+impl ContentCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        let inner = match config.capacity {
+            0 => None,
+            capacity => Some(Mutex::new(CacheInner {
+                entries: LruCache::new(capacity),
+                generations: HashMap::new(),
+                epoch: 0,
+            })),
+        };
+        Self {
+            inner,
+            ttl: Duration::from_secs(config.ttl),
+        }
+    }
+
+    pub fn get(&self, id: u32) -> Option<Arc<Localized>> {
+        let mut inner = self.inner.as_ref()?.lock().unwrap();
+        let content = match inner.entries.get(&id) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.content.clone()),
+            Some(_) => {
+                inner.entries.remove(&id);
+                None
+            }
+            None => None,
+        };
+        let result = if content.is_some() { "hit" } else { "miss" };
+        metrics::counter!("crm_metadata_cache_total", "result" => result).increment(1);
+        content
+    }
+
+    /// start loading the content, to be cached with [`Load::insert`] once it's loaded
+    pub fn start_load(&self, id: u32) -> Load<'_> {
+        let generation = self.inner.as_ref().map(|inner| {
+            let mut inner = inner.lock().unwrap();
+            let epoch = inner.epoch;
+            let loading = inner.generations.entry(id).or_default();
+            loading.loads += 1;
+            Generation {
+                id,
+                epoch,
+                generation: loading.generation,
+            }
+        });
+        Load {
+            cache: self,
+            generation,
+        }
+    }
+
+    /// drop the contents that are changed, so they're read again on next use
+    pub fn invalidate(&self, ids: impl IntoIterator<Item = u32>) {
+        if let Some(inner) = &self.inner {
+            let mut inner = inner.lock().unwrap();
+            for id in ids {
+                inner.entries.remove(&id);
+                if let Some(loading) = inner.generations.get_mut(&id) {
+                    loading.generation += 1;
+                }
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Some(inner) = &self.inner {
+            let mut inner = inner.lock().unwrap();
+            inner.entries.clear();
+            inner.epoch += 1;
+        }
+    }
+}
+
+impl Load<'_> {
+    /// cache the loaded content, unless it was invalidated since the load started
+    pub fn insert(mut self, content: Arc<Localized>) {
+        let (Some(inner), Some(generation)) = (&self.cache.inner, self.generation.take()) else {
+            return;
+        };
+        let mut inner = inner.lock().unwrap();
+        if inner.finish(generation) {
+            let entry = Entry {
+                content,
+                expires_at: Instant::now() + self.cache.ttl,
+            };
+            inner.entries.insert(generation.id, entry);
+        }
+    }
+}
+
+impl Drop for Load<'_> {
+    /// the load failed or was cancelled, nothing is cached
+    fn drop(&mut self) {
+        if let (Some(inner), Some(generation)) = (&self.cache.inner, self.generation.take()) {
+            inner.lock().unwrap().finish(generation);
+        }
+    }
+}
+
+impl CacheInner {
+    /// end the load; returns whether it's still fresh
+    fn finish(&mut self, generation: Generation) -> bool {
+        let Some(loading) = self.generations.get_mut(&generation.id) else {
+            return false;
+        };
+        let fresh = generation.epoch == self.epoch && generation.generation == loading.generation;
+        loading.loads -= 1;
+        if loading.loads == 0 {
+            self.generations.remove(&generation.id);
+        }
+        fresh
+    }
+}
+
+impl MetadataService {
+    /// get the content in the locale from the cache, or from the catalog if it's not there
+    pub(super) async fn cached_content(&self, id: u32, locale: &str) -> Result<Content, Status> {
+        let content = match self.cache.get(id) {
+            Some(content) => content,
+            None => {
+                let load = self.cache.start_load(id);
+                let content = Arc::new(self.catalog.get_localized(id).await?);
+                load.insert(content.clone());
+                content
+            }
+        };
+        Ok(content.localize(locale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::{GetContentRequest, UpdateContentRequest, UpdatePublisherRequest};
+    use anyhow::Result;
+
+    fn content(id: u32, name: &str) -> Arc<Localized> {
+        let content = Content {
+            id,
+            name: name.to_string(),
+            ..Default::default()
+        };
+        Arc::new(content.into())
+    }
+
+    #[test]
+    fn cache_should_evict_least_recently_used() {
+        let cache = ContentCache::new(&CacheConfig {
+            capacity: 2,
+            ttl: 60,
+        });
+        cache.start_load(1).insert(content(1, "a"));
+        cache.start_load(2).insert(content(2, "b"));
+        assert!(cache.get(1).is_some());
+        cache.start_load(3).insert(content(3, "c"));
+
+        assert!(cache.get(2).is_none());
+        assert_eq!(cache.get(1).unwrap().content.name, "a");
+        assert_eq!(cache.get(3).unwrap().content.name, "c");
+    }
+
+    #[test]
+    fn cache_should_expire_and_invalidate() {
+        let cache = ContentCache::new(&CacheConfig {
+            capacity: 10,
+            ttl: 0,
+        });
+        cache.start_load(1).insert(content(1, "a"));
+        assert!(cache.get(1).is_none());
+
+        let cache = ContentCache::new(&CacheConfig {
+            capacity: 10,
+            ttl: 60,
+        });
+        cache.start_load(1).insert(content(1, "a"));
+        cache.start_load(2).insert(content(2, "b"));
+        cache.invalidate([1]);
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(2).is_some());
+    }
+
+    #[test]
+    fn stale_loads_should_not_be_cached() {
+        let cache = ContentCache::new(&CacheConfig {
+            capacity: 10,
+            ttl: 60,
+        });
+        let load = cache.start_load(1);
+        cache.invalidate([1]);
+        load.insert(content(1, "stale"));
+        assert!(cache.get(1).is_none());
+
+        let load = cache.start_load(1);
+        cache.clear();
+        load.insert(content(1, "stale"));
+        assert!(cache.get(1).is_none());
+
+        // a failed load is forgotten
+        drop(cache.start_load(2));
+        let load = cache.start_load(1);
+        cache.invalidate([2]);
+        load.insert(content(1, "fresh"));
+        assert_eq!(cache.get(1).unwrap().content.name, "fresh");
+        assert!(cache
+            .inner
+            .as_ref()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .generations
+            .is_empty());
+    }
+
+    #[test]
+    fn cache_should_be_disabled_without_capacity() {
+        let cache = ContentCache::new(&CacheConfig {
+            capacity: 0,
+            ttl: 60,
+        });
+        cache.start_load(1).insert(content(1, "a"));
+        assert!(cache.get(1).is_none());
+    }
+
+    #[tokio::test]
+    async fn cached_content_should_be_invalidated_on_change() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+        let get = |id| {
+            let service = service.clone();
+            let req = GetContentRequest {
+                id,
+                ..Default::default()
+            };
+            async move { service.get_content(req).await }
+        };
+        let content = get(1).await?.into_inner();
+
+        // changed behind the service's back, the cached one is served
+        let pool = service.catalog.pool().unwrap();
+        sqlx::query("UPDATE contents SET name = 'Changed' WHERE id = 1")
+            .execute(pool)
+            .await?;
+        assert_eq!(get(1).await?.into_inner(), content);
+
+        let req = UpdateContentRequest {
+            id: 1,
+            version: content.version,
+            name: "Updated".to_string(),
+            description: content.description.clone(),
+            url: content.url.clone(),
+            image: content.image.clone(),
+            r#type: content.r#type,
+        };
+        service.update_content(req).await?;
+        assert_eq!(get(1).await?.into_inner().name, "Updated");
+
+        // the contents of an updated publisher are read again too
+        let publisher = &content.publishers[0];
+        let req = UpdatePublisherRequest {
+            id: publisher.id,
+            version: publisher.version,
+            name: "Renamed".to_string(),
+            avatar: publisher.avatar.clone(),
+        };
+        service.update_publisher(req).await?;
+        assert_eq!(get(1).await?.into_inner().publishers[0].name, "Renamed");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use std::collections::HashMap;
+use tonic::Status;
+use tracing::{error, info};
+
+use crate::{
+    config::CatalogConfig,
+    pb::{Content, ContentStatus, ContentType, Publisher, Translation},
+};
+
+use super::{locale::Localized, to_ts};
+
+/// where the contents are read from
+pub enum Catalog {
+    /// make up the contents from the seed
+    Fake(u64),
+    Postgres(PgPool),
+}
+
+/// the columns of a [`ContentRow`] in the contents table
+pub(super) const CONTENT_COLUMNS: &str =
+    "id, name, description, url, image, type::text AS type, created_at, views, likes, dislikes, version, status::text AS status, available_from, available_until";
+
+#[derive(Debug, FromRow)]
+pub(super) struct ContentRow {
+    id: i64,
+    name: String,
+    description: String,
+    url: String,
+    image: String,
+    r#type: String,
+    created_at: DateTime<Utc>,
+    views: i64,
+    likes: i64,
+    dislikes: i64,
+    version: i64,
+    status: String,
+    available_from: Option<DateTime<Utc>>,
+    available_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+pub(super) struct PublisherRow {
+    id: i64,
+    name: String,
+    avatar: String,
+    version: i64,
+}
+
+#[derive(Debug, FromRow)]
+struct LinkedPublisherRow {
+    content_id: i64,
+    #[sqlx(flatten)]
+    publisher: PublisherRow,
+}
+
+impl Catalog {
+    pub async fn new(config: &CatalogConfig) -> Result<Self> {
+        match config {
+            CatalogConfig::Fake { seed } => {
+                info!("Using fake catalog with seed {}", seed);
+                Ok(Catalog::Fake(*seed))
+            }
+            CatalogConfig::Postgres { db_url } => {
+                let pool = PgPool::connect(db_url)
+                    .await
+                    .context("Failed to connect to db")?;
+                sqlx::migrate!()
+                    .run(&pool)
+                    .await
+                    .context("Failed to run migrations")?;
+                Ok(Catalog::Postgres(pool))
+            }
+        }
+    }
+
+    /// the pool to edit the catalog, the fake one is read only
+    pub fn pool(&self) -> Option<&PgPool> {
+        match self {
+            Catalog::Fake(_) => None,
+            Catalog::Postgres(pool) => Some(pool),
+        }
+    }
+
+    /// get the content with its publishers, NOT_FOUND if there's no such content
+    pub async fn get(&self, id: u32) -> Result<Content, Status> {
+        let pool = match self {
+            Catalog::Fake(seed) => return Ok(Content::materialize(*seed, id)),
+            Catalog::Postgres(pool) => pool,
+        };
+
+        let sql = format!("SELECT {} FROM contents WHERE id = $1", CONTENT_COLUMNS);
+        let row: Option<ContentRow> = sqlx::query_as(&sql)
+            .bind(id as i64)
+            .fetch_optional(pool)
+            .await
+            .map_err(internal)?;
+        let Some(row) = row else {
+            return Err(Status::not_found(format!("Content {} not found", id)));
+        };
+
+        let mut contents = with_publishers(pool, vec![row]).await?;
+        Ok(contents.remove(0))
+    }
+
+    /// get the content along with its translations
+    pub async fn get_localized(&self, id: u32) -> Result<Localized, Status> {
+        let content = self.get(id).await?;
+        let Catalog::Postgres(pool) = self else {
+            return Ok(content.into());
+        };
+
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT locale, name, description FROM content_translations WHERE content_id = $1",
+        )
+        .bind(id as i64)
+        .fetch_all(pool)
+        .await
+        .map_err(internal)?;
+        let translations = rows
+            .into_iter()
+            .map(|(locale, name, description)| {
+                let translation = Translation {
+                    locale: locale.clone(),
+                    name,
+                    description,
+                };
+                (locale, translation)
+            })
+            .collect();
+        Ok(Localized {
+            content,
+            translations,
+        })
+    }
+
+    /// get the publisher, NOT_FOUND if there's no such publisher
+    pub async fn get_publisher(&self, id: u32) -> Result<Publisher, Status> {
+        let pool = match self {
+            Catalog::Fake(seed) => return Ok(Publisher::materialize(*seed, id)),
+            Catalog::Postgres(pool) => pool,
+        };
+
+        let row: Option<PublisherRow> =
+            sqlx::query_as("SELECT id, name, avatar, version FROM publishers WHERE id = $1")
+                .bind(id as i64)
+                .fetch_optional(pool)
+                .await
+                .map_err(internal)?;
+        match row {
+            Some(row) => Ok(row.into()),
+            None => Err(Status::not_found(format!("Publisher {} not found", id))),
+        }
+    }
+}
+
+/// turn the rows into contents, along with their publishers
+pub(super) async fn with_publishers(
+    pool: &PgPool,
+    rows: Vec<ContentRow>,
+) -> Result<Vec<Content>, Status> {
+    let ids: Vec<i64> = rows.iter().map(|row| row.id).collect();
+    let linked: Vec<LinkedPublisherRow> = sqlx::query_as(
+        "SELECT cp.content_id, p.id, p.name, p.avatar, p.version FROM content_publishers cp JOIN publishers p ON p.id = cp.publisher_id WHERE cp.content_id = ANY($1) ORDER BY cp.content_id, cp.position",
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await
+    .map_err(internal)?;
+
+    let mut publishers: HashMap<i64, Vec<PublisherRow>> = HashMap::new();
+    for row in linked {
+        publishers
+            .entry(row.content_id)
+            .or_default()
+            .push(row.publisher);
+    }
+    let contents = rows
+        .into_iter()
+        .map(|row| {
+            let publishers = publishers.remove(&row.id).unwrap_or_default();
+            row.into_content(publishers)
+        })
+        .collect();
+    Ok(contents)
+}
+
+impl ContentRow {
+    fn into_content(self, publishers: Vec<PublisherRow>) -> Content {
+        let content_type = parse_type(&self.r#type).unwrap_or_default();
+        let status = parse_status(&self.status).unwrap_or_default();
+        Content {
+            id: self.id as u32,
+            name: self.name,
+            description: self.description,
+            publishers: publishers.into_iter().map(Publisher::from).collect(),
+            url: self.url,
+            image: self.image,
+            r#type: content_type as i32,
+            created_at: Some(to_ts(self.created_at)),
+            views: self.views as u64,
+            likes: self.likes as u64,
+            dislikes: self.dislikes as u64,
+            version: self.version as u64,
+            locale: String::new(),
+            status: status as i32,
+            available_from: self.available_from.map(to_ts),
+            available_until: self.available_until.map(to_ts),
+        }
+    }
+}
+
+impl From<PublisherRow> for Publisher {
+    fn from(row: PublisherRow) -> Self {
+        Publisher {
+            id: row.id as u32,
+            name: row.name,
+            avatar: row.avatar,
+            version: row.version as u64,
+        }
+    }
+}
+
+/// the name of the type in the content_type enum of the db
+pub(super) fn type_name(content_type: ContentType) -> String {
+    content_type
+        .as_str_name()
+        .trim_start_matches("CONTENT_TYPE_")
+        .to_lowercase()
+}
+
+/// the type of the name in the content_type enum of the db
+pub(super) fn parse_type(name: &str) -> Option<ContentType> {
+    ContentType::from_str_name(&format!("CONTENT_TYPE_{}", name.to_uppercase()))
+}
+
+/// the name of the status in the content_status enum of the db
+pub(super) fn status_name(status: ContentStatus) -> String {
+    status
+        .as_str_name()
+        .trim_start_matches("CONTENT_STATUS_")
+        .to_lowercase()
+}
+
+pub(super) fn parse_status(name: &str) -> Option<ContentStatus> {
+    ContentStatus::from_str_name(&format!("CONTENT_STATUS_{}", name.to_uppercase()))
+}
+
+pub(super) fn read_only() -> Status {
+    Status::failed_precondition("The fake catalog is read only")
+}
+
+/// the db error is only logged, as it may tell about the schema or the data
+pub(super) fn internal(e: sqlx::Error) -> Status {
+    error!("Failed to query catalog: {:?}", e);
+    Status::internal("Failed to query catalog")
+}
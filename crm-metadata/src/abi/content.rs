@@ -0,0 +1,412 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use tonic::{Response, Status};
+use tracing::info;
+use url::Url;
+
+use crate::{
+    pb::{
+        Content, ContentType, CreateContentRequest, DeleteContentRequest, DeleteContentResponse,
+        GetContentRequest, LinkPublisherRequest, UnlinkPublisherRequest, UpdateContentRequest,
+    },
+    MetadataService, ServiceResult,
+};
+
+use super::catalog::{internal, read_only, type_name};
+
+pub(super) const MAX_NAME_LEN: usize = 256;
+const MAX_URL_LEN: usize = 256;
+
+impl MetadataService {
+    pub async fn get_content(&self, req: GetContentRequest) -> ServiceResult<Content> {
+        let content = self.cached_content(req.id, &req.locale).await?;
+        Ok(Response::new(content))
+    }
+
+    pub async fn create_content(&self, req: CreateContentRequest) -> ServiceResult<Content> {
+        validate_content(&req.name, &req.url, &req.image, req.r#type)
+            .map_err(Status::invalid_argument)?;
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+
+        let mut tx = pool.begin().await.map_err(internal)?;
+        let (id,): (i64,) = sqlx::query_as(
+            "INSERT INTO contents (name, description, url, image, type) VALUES ($1, $2, $3, $4, $5::content_type) RETURNING id",
+        )
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.url)
+        .bind(&req.image)
+        .bind(type_name(req.r#type()))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(internal)?;
+
+        for publisher_id in req.publisher_ids.iter() {
+            link(&mut tx, id, *publisher_id).await?;
+        }
+        tx.commit().await.map_err(internal)?;
+
+        info!("Content {} created", id);
+        let content = self.catalog.get(id as u32).await?;
+        Ok(Response::new(content))
+    }
+
+    pub async fn update_content(&self, req: UpdateContentRequest) -> ServiceResult<Content> {
+        validate_content(&req.name, &req.url, &req.image, req.r#type)
+            .map_err(Status::invalid_argument)?;
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+
+        let ret = sqlx::query(
+            "UPDATE contents SET name = $3, description = $4, url = $5, image = $6, type = $7::content_type, version = version + 1 WHERE id = $1 AND version = $2",
+        )
+        .bind(req.id as i64)
+        .bind(req.version as i64)
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(&req.url)
+        .bind(&req.image)
+        .bind(type_name(req.r#type()))
+        .execute(pool)
+        .await
+        .map_err(internal)?;
+        if ret.rows_affected() == 0 {
+            return Err(version_conflict(pool, "contents", "Content", req.id, req.version).await);
+        }
+
+        info!("Content {} updated", req.id);
+        self.cache.invalidate([req.id]);
+        let content = self.catalog.get(req.id).await?;
+        Ok(Response::new(content))
+    }
+
+    pub async fn delete_content(
+        &self,
+        req: DeleteContentRequest,
+    ) -> ServiceResult<DeleteContentResponse> {
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+        let ret = sqlx::query("DELETE FROM contents WHERE id = $1")
+            .bind(req.id as i64)
+            .execute(pool)
+            .await
+            .map_err(internal)?;
+        if ret.rows_affected() == 0 {
+            return Err(Status::not_found(format!("Content {} not found", req.id)));
+        }
+
+        info!("Content {} deleted", req.id);
+        self.cache.invalidate([req.id]);
+        Ok(Response::new(DeleteContentResponse { id: req.id }))
+    }
+
+    pub async fn link_publisher(&self, req: LinkPublisherRequest) -> ServiceResult<Content> {
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+        let mut tx = pool.begin().await.map_err(internal)?;
+        lock_content(&mut tx, req.content_id).await?;
+        link(&mut tx, req.content_id as i64, req.publisher_id).await?;
+        bump_version(&mut tx, req.content_id as i64).await?;
+        tx.commit().await.map_err(internal)?;
+        self.cache.invalidate([req.content_id]);
+
+        let content = self.catalog.get(req.content_id).await?;
+        Ok(Response::new(content))
+    }
+
+    pub async fn unlink_publisher(&self, req: UnlinkPublisherRequest) -> ServiceResult<Content> {
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+        let mut tx = pool.begin().await.map_err(internal)?;
+        lock_content(&mut tx, req.content_id).await?;
+        let ret = sqlx::query(
+            "DELETE FROM content_publishers WHERE content_id = $1 AND publisher_id = $2",
+        )
+        .bind(req.content_id as i64)
+        .bind(req.publisher_id as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+        if ret.rows_affected() == 0 {
+            return Err(Status::not_found(format!(
+                "Publisher {} is not linked to content {}",
+                req.publisher_id, req.content_id
+            )));
+        }
+        bump_version(&mut tx, req.content_id as i64).await?;
+        tx.commit().await.map_err(internal)?;
+        self.cache.invalidate([req.content_id]);
+
+        let content = self.catalog.get(req.content_id).await?;
+        Ok(Response::new(content))
+    }
+}
+
+/// add the publisher to the end of the content's publishers
+pub(super) async fn link(
+    tx: &mut Transaction<'_, Postgres>,
+    content_id: i64,
+    publisher_id: u32,
+) -> Result<(), Status> {
+    let exists: Option<(i64,)> = sqlx::query_as("SELECT id FROM publishers WHERE id = $1")
+        .bind(publisher_id as i64)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(internal)?;
+    if exists.is_none() {
+        return Err(Status::not_found(format!(
+            "Publisher {} not found",
+            publisher_id
+        )));
+    }
+
+    let ret = sqlx::query(
+        "INSERT INTO content_publishers (content_id, publisher_id, position) SELECT $1, $2, COALESCE(MAX(position) + 1, 0) FROM content_publishers WHERE content_id = $1 ON CONFLICT DO NOTHING",
+    )
+    .bind(content_id)
+    .bind(publisher_id as i64)
+    .execute(&mut **tx)
+    .await
+    .map_err(internal)?;
+    if ret.rows_affected() == 0 {
+        return Err(Status::already_exists(format!(
+            "Publisher {} is already linked to content {}",
+            publisher_id, content_id
+        )));
+    }
+    Ok(())
+}
+
+/// lock the content row, so concurrent links get consecutive positions
+pub(super) async fn lock_content(
+    tx: &mut Transaction<'_, Postgres>,
+    id: u32,
+) -> Result<(), Status> {
+    let exists: Option<(i64,)> = sqlx::query_as("SELECT id FROM contents WHERE id = $1 FOR UPDATE")
+        .bind(id as i64)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(internal)?;
+    match exists {
+        Some(_) => Ok(()),
+        None => Err(Status::not_found(format!("Content {} not found", id))),
+    }
+}
+
+pub(super) async fn bump_version(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i64,
+) -> Result<(), Status> {
+    sqlx::query("UPDATE contents SET version = version + 1 WHERE id = $1")
+        .bind(id)
+        .execute(&mut **tx)
+        .await
+        .map_err(internal)?;
+    Ok(())
+}
+
+/// the error of an update that changed nothing: either the row is gone, or it's at another
+/// version than the update is based on
+pub(super) async fn version_conflict(
+    pool: &PgPool,
+    table: &str,
+    kind: &str,
+    id: u32,
+    version: u64,
+) -> Status {
+    let sql = format!("SELECT version FROM {} WHERE id = $1", table);
+    let current: Result<Option<(i64,)>, _> = sqlx::query_as(&sql)
+        .bind(id as i64)
+        .fetch_optional(pool)
+        .await;
+    match current {
+        Ok(Some((current,))) => Status::aborted(format!(
+            "{} {} is at version {}, not {}",
+            kind, id, current, version
+        )),
+        Ok(None) => Status::not_found(format!("{} {} not found", kind, id)),
+        Err(e) => internal(e),
+    }
+}
+
+pub(super) fn validate_content(
+    name: &str,
+    url: &str,
+    image: &str,
+    content_type: i32,
+) -> Result<(), String> {
+    validate_name(name, MAX_NAME_LEN)?;
+    validate_url("url", url)?;
+    if !image.is_empty() {
+        validate_url("image", image)?;
+    }
+    match ContentType::try_from(content_type) {
+        Ok(ContentType::Unspecified) => Err("Content type is required".to_string()),
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("Invalid content type: {}", content_type)),
+    }
+}
+
+pub(super) fn validate_name(name: &str, max: usize) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Name is required".to_string());
+    }
+    if name.chars().count() > max {
+        return Err(format!("Name is longer than {} characters", max));
+    }
+    Ok(())
+}
+
+/// only absolute http(s) urls are allowed
+pub(super) fn validate_url(field: &str, url: &str) -> Result<(), String> {
+    if url.len() > MAX_URL_LEN {
+        return Err(format!(
+            "Invalid {}: longer than {} characters",
+            field, MAX_URL_LEN
+        ));
+    }
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => Ok(()),
+        Ok(_) => Err(format!(
+            "Invalid {}: {:?} is not an http(s) url",
+            field, url
+        )),
+        Err(e) => Err(format!("Invalid {}: {:?}: {}", field, url, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tonic::Code;
+
+    fn create_req() -> CreateContentRequest {
+        CreateContentRequest {
+            name: "Rust in Action".to_string(),
+            description: "A hands-on guide".to_string(),
+            url: "https://acme.org/contents/rust".to_string(),
+            image: String::new(),
+            r#type: ContentType::Vlog as i32,
+            publisher_ids: vec![3, 1],
+        }
+    }
+
+    #[test]
+    fn validate_content_should_work() {
+        let req = create_req();
+        assert!(validate_content(&req.name, &req.url, &req.image, req.r#type).is_ok());
+
+        let cases = [
+            (" ", req.url.as_str(), "", req.r#type, "Name is required"),
+            (&req.name, "ftp://acme.org/a", "", req.r#type, "Invalid url"),
+            (&req.name, "acme.org/a", "", req.r#type, "Invalid url"),
+            (
+                &req.name,
+                &req.url,
+                "not a url",
+                req.r#type,
+                "Invalid image",
+            ),
+            (&req.name, &req.url, "", 0, "Content type is required"),
+            (&req.name, &req.url, "", 42, "Invalid content type: 42"),
+        ];
+        for (name, url, image, content_type, msg) in cases {
+            let e = validate_content(name, url, image, content_type).unwrap_err();
+            assert!(e.starts_with(msg), "{}", e);
+        }
+    }
+
+    #[tokio::test]
+    async fn content_crud_should_work() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+
+        let content = service.create_content(create_req()).await?.into_inner();
+        assert_eq!(content.version, 1);
+        assert_eq!(content.r#type(), ContentType::Vlog);
+        let publishers: Vec<_> = content.publishers.iter().map(|p| p.id).collect();
+        assert_eq!(publishers, [3, 1]);
+
+        let req = UpdateContentRequest {
+            id: content.id,
+            version: content.version,
+            name: "Rust in Action, 2nd edition".to_string(),
+            description: content.description.clone(),
+            url: content.url.clone(),
+            image: "https://placehold.co/1600x900".to_string(),
+            r#type: ContentType::Movie as i32,
+        };
+        let updated = service.update_content(req.clone()).await?.into_inner();
+        assert_eq!(updated.version, 2);
+        assert_eq!(updated.name, "Rust in Action, 2nd edition");
+        assert_eq!(updated.r#type(), ContentType::Movie);
+
+        // based on a stale version
+        let e = service.update_content(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::Aborted);
+        assert_eq!(
+            e.message(),
+            format!("Content {} is at version 2, not 1", content.id)
+        );
+
+        let req = GetContentRequest {
+            id: content.id,
+            ..Default::default()
+        };
+        let got = service.get_content(req.clone()).await?.into_inner();
+        assert_eq!(got, updated);
+
+        let del = DeleteContentRequest { id: content.id };
+        service.delete_content(del.clone()).await?;
+        let e = service.get_content(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+        let e = service.delete_content(del).await.unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+
+        let mut req = create_req();
+        req.publisher_ids = vec![1000];
+        let e = service.create_content(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+        assert_eq!(e.message(), "Publisher 1000 not found");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn link_publisher_should_work() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+        let content = service
+            .get_content(GetContentRequest {
+                id: 1,
+                ..Default::default()
+            })
+            .await?
+            .into_inner();
+        let publishers: Vec<_> = content.publishers.iter().map(|p| p.id).collect();
+        assert_eq!(publishers, [1, 5, 2]);
+
+        let req = LinkPublisherRequest {
+            content_id: 1,
+            publisher_id: 8,
+        };
+        let linked = service.link_publisher(req.clone()).await?.into_inner();
+        let publishers: Vec<_> = linked.publishers.iter().map(|p| p.id).collect();
+        assert_eq!(publishers, [1, 5, 2, 8]);
+        assert_eq!(linked.version, content.version + 1);
+        let e = service.link_publisher(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::AlreadyExists);
+
+        let req = UnlinkPublisherRequest {
+            content_id: 1,
+            publisher_id: 5,
+        };
+        let unlinked = service.unlink_publisher(req.clone()).await?.into_inner();
+        let publishers: Vec<_> = unlinked.publishers.iter().map(|p| p.id).collect();
+        assert_eq!(publishers, [1, 2, 8]);
+        let e = service.unlink_publisher(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fake_catalog_should_be_read_only() -> Result<()> {
+        let service = MetadataService::new_fake(42).await?;
+        let e = service.create_content(create_req()).await.unwrap_err();
+        assert_eq!(e.code(), Code::FailedPrecondition);
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use tonic::{Response, Status};
+use tracing::info;
+
+use crate::{
+    pb::{Content, DeleteTranslationRequest, PutTranslationRequest, Translation},
+    MetadataService, ServiceResult,
+};
+
+use super::{
+    catalog::{internal, read_only},
+    content::{bump_version, lock_content, validate_name, MAX_NAME_LEN},
+};
+
+const MAX_LOCALE_LEN: usize = 16;
+
+/// a content along with its translations, keyed by the normalized locale
+#[derive(Debug)]
+pub struct Localized {
+    pub(super) content: Content,
+    pub(super) translations: HashMap<String, Translation>,
+}
+
+impl Localized {
+    /// the content with the name and description of the closest translation to the locale
+    pub fn localize(&self, locale: &str) -> Content {
+        let mut content = self.content.clone();
+        let translation = fallbacks(locale)
+            .iter()
+            .find_map(|locale| self.translations.get(locale));
+        if let Some(translation) = translation {
+            content.name = translation.name.clone();
+            content.description = translation.description.clone();
+            content.locale = translation.locale.clone();
+        }
+        content
+    }
+}
+
+impl From<Content> for Localized {
+    fn from(content: Content) -> Self {
+        Self {
+            content,
+            translations: HashMap::new(),
+        }
+    }
+}
+
+impl MetadataService {
+    pub async fn put_translation(&self, req: PutTranslationRequest) -> ServiceResult<Content> {
+        let translation = req
+            .translation
+            .ok_or_else(|| Status::invalid_argument("Translation is required"))?;
+        let locale = normalize(&translation.locale);
+        validate_locale(&locale).map_err(Status::invalid_argument)?;
+        validate_name(&translation.name, MAX_NAME_LEN).map_err(Status::invalid_argument)?;
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+
+        let mut tx = pool.begin().await.map_err(internal)?;
+        lock_content(&mut tx, req.content_id).await?;
+        sqlx::query(
+            "INSERT INTO content_translations (content_id, locale, name, description) VALUES ($1, $2, $3, $4) ON CONFLICT (content_id, locale) DO UPDATE SET name = EXCLUDED.name, description = EXCLUDED.description",
+        )
+        .bind(req.content_id as i64)
+        .bind(&locale)
+        .bind(&translation.name)
+        .bind(&translation.description)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+        bump_version(&mut tx, req.content_id as i64).await?;
+        tx.commit().await.map_err(internal)?;
+
+        info!("Content {} translated to {}", req.content_id, locale);
+        self.cache.invalidate([req.content_id]);
+        let content = self.cached_content(req.content_id, &locale).await?;
+        Ok(Response::new(content))
+    }
+
+    pub async fn delete_translation(
+        &self,
+        req: DeleteTranslationRequest,
+    ) -> ServiceResult<Content> {
+        let locale = normalize(&req.locale);
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+
+        let mut tx = pool.begin().await.map_err(internal)?;
+        lock_content(&mut tx, req.content_id).await?;
+        let ret =
+            sqlx::query("DELETE FROM content_translations WHERE content_id = $1 AND locale = $2")
+                .bind(req.content_id as i64)
+                .bind(&locale)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal)?;
+        if ret.rows_affected() == 0 {
+            return Err(Status::not_found(format!(
+                "Content {} has no translation in {}",
+                req.content_id, locale
+            )));
+        }
+        bump_version(&mut tx, req.content_id as i64).await?;
+        tx.commit().await.map_err(internal)?;
+
+        self.cache.invalidate([req.content_id]);
+        let content = self.cached_content(req.content_id, "").await?;
+        Ok(Response::new(content))
+    }
+}
+
+/// locales are kept in lower case with '-' between the subtags, e.g. zh_CN is zh-cn
+pub(super) fn normalize(locale: &str) -> String {
+    locale.trim().to_lowercase().replace('_', "-")
+}
+
+/// the locales to look for, the most specific first: zh-hant-tw, zh-hant, zh
+fn fallbacks(locale: &str) -> Vec<String> {
+    let locale = normalize(locale);
+    let mut locales = Vec::new();
+    let mut rest = locale.as_str();
+    while !rest.is_empty() {
+        locales.push(rest.to_string());
+        rest = rest.rsplit_once('-').map_or("", |(parent, _)| parent);
+    }
+    locales
+}
+
+pub(super) fn validate_locale(locale: &str) -> Result<(), String> {
+    let valid = !locale.is_empty()
+        && locale.len() <= MAX_LOCALE_LEN
+        && locale
+            .split('-')
+            .all(|tag| !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric()));
+    if !valid {
+        return Err(format!("Invalid locale: {}", locale));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::{GetContentRequest, MaterializeRequest};
+    use anyhow::Result;
+    use futures::StreamExt;
+    use tonic::Code;
+
+    #[test]
+    fn fallbacks_should_go_from_specific_to_general() {
+        assert_eq!(fallbacks("zh_Hant_TW"), ["zh-hant-tw", "zh-hant", "zh"]);
+        assert_eq!(fallbacks("en"), ["en"]);
+        assert!(fallbacks("").is_empty());
+    }
+
+    #[test]
+    fn localize_should_use_closest_translation() {
+        let mut content = Localized::from(Content {
+            id: 1,
+            name: "Sit Lorem Sed".to_string(),
+            ..Default::default()
+        });
+        let translation = Translation {
+            locale: "zh".to_string(),
+            name: "坐下".to_string(),
+            description: "".to_string(),
+        };
+        content.translations.insert("zh".to_string(), translation);
+
+        let localized = content.localize("zh-CN");
+        assert_eq!(localized.name, "坐下");
+        assert_eq!(localized.locale, "zh");
+        let localized = content.localize("fr");
+        assert_eq!(localized.name, "Sit Lorem Sed");
+        assert_eq!(localized.locale, "");
+    }
+
+    #[tokio::test]
+    async fn translations_should_be_materialized() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+        let req = PutTranslationRequest {
+            content_id: 1,
+            translation: Some(Translation {
+                locale: "zh_CN".to_string(),
+                name: "坐下".to_string(),
+                description: "简介".to_string(),
+            }),
+        };
+        let content = service.put_translation(req).await?.into_inner();
+        assert_eq!(content.name, "坐下");
+        assert_eq!(content.locale, "zh-cn");
+        assert_eq!(content.version, 2);
+
+        let stream = tokio_stream::iter(vec![
+            Ok(MaterializeRequest::new(1, "zh-CN")),
+            Ok(MaterializeRequest::new(1, "zh")),
+            Ok(MaterializeRequest::new(1, "")),
+        ]);
+        let ret: Vec<_> = service
+            .materialize(stream)
+            .await?
+            .into_inner()
+            .collect()
+            .await;
+        let names: Vec<_> = ret
+            .iter()
+            .map(|c| c.as_ref().unwrap().name.as_str())
+            .collect();
+        assert_eq!(names, ["坐下", "Sit Lorem Sed", "Sit Lorem Sed"]);
+
+        let req = DeleteTranslationRequest {
+            content_id: 1,
+            locale: "zh-cn".to_string(),
+        };
+        service.delete_translation(req.clone()).await?;
+        let req2 = GetContentRequest {
+            id: 1,
+            locale: "zh-CN".to_string(),
+        };
+        let content = service.get_content(req2).await?.into_inner();
+        assert_eq!(content.name, "Sit Lorem Sed");
+        let e = service.delete_translation(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+
+        let req = PutTranslationRequest {
+            content_id: 1,
+            translation: Some(Translation {
+                locale: "zh cn".to_string(),
+                name: "坐下".to_string(),
+                description: "".to_string(),
+            }),
+        };
+        let e = service.put_translation(req).await.unwrap_err();
+        assert_eq!(e.message(), "Invalid locale: zh cn");
+
+        Ok(())
+    }
+}
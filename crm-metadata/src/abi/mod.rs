@@ -1,20 +1,35 @@
+mod availability;
+mod batch;
+mod bulk;
+mod cache;
+mod catalog;
+mod content;
+mod locale;
+mod publisher;
+mod recommend;
+mod search;
+
+pub use cache::ContentCache;
+pub use catalog::Catalog;
+
 use std::{collections::HashSet, fmt};
 
 use crate::{
-    pb::{Content, MaterializeRequest, Publisher, UnfinishedContents},
+    pb::{Content, ContentStatus, ContentType, MaterializeRequest, Publisher, UnfinishedContents},
     MetadataService, ResponseStream, ServiceResult,
 };
-use chrono::{DateTime, Days, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use fake::{
     faker::{chrono::en::DateTimeBetween, lorem::en::Sentence, name::en::Name},
-    Fake, Faker,
+    Fake,
 };
 use futures::{stream, Stream, StreamExt};
 use prost_types::Timestamp;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Response, Status};
+use tracing::{info, warn};
 
 const CHANNEL_SIZE: usize = 1024;
 
@@ -24,10 +39,31 @@ impl MetadataService {
         mut stream: impl Stream<Item = Result<MaterializeRequest, Status>> + Send + 'static + Unpin,
     ) -> ServiceResult<ResponseStream> {
         let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+        let svc = self.clone();
         tokio::spawn(async move {
-            while let Some(Ok(req)) = stream.next().await {
-                let content = Content::materialize(req.id);
-                tx.send(Ok(content)).await.unwrap();
+            loop {
+                let req = tokio::select! {
+                    _ = tx.closed() => {
+                        info!("Client disconnected, stop materializing");
+                        break;
+                    }
+                    req = stream.next() => match req {
+                        Some(req) => req,
+                        None => break,
+                    },
+                };
+
+                let res = match req {
+                    Ok(req) => svc.materialize_one(req).await,
+                    Err(e) => {
+                        warn!("Failed to receive request: {:?}", e);
+                        Err(e)
+                    }
+                };
+                if tx.send(res).await.is_err() {
+                    info!("Client disconnected, stop materializing");
+                    break;
+                }
             }
         });
 
@@ -37,25 +73,32 @@ impl MetadataService {
 }
 
 impl Content {
-    pub fn materialize(id: u32) -> Self {
-        let mut rng = rand::thread_rng();
+    /// make up a content, the same for the same seed and id
+    pub fn materialize(seed: u64, id: u32) -> Self {
+        let mut rng = seeded_rng(seed, id);
         let views = rng.gen_range(1..10000000);
         let likes = (views as f64 * rng.gen_range(0.0..0.5)).floor() as u64;
         let dislikes = (views as f64 * rng.gen_range(0.0..0.5)).floor() as u64;
+        let publishers = (1..rng.gen_range(2..10))
+            .map(|_| Publisher::materialize(seed, rng.gen_range(10000..2000000)))
+            .collect();
         Content {
             id,
-            name: Name().fake(),
-            description: Sentence(3..7).fake(),
-            publishers: (1..rng.gen_range(2..10))
-                .map(|_| Publisher::new())
-                .collect(),
+            name: Name().fake_with_rng(&mut rng),
+            description: Sentence(3..7).fake_with_rng(&mut rng),
+            publishers,
             url: "https://placehold.co/1600x900".to_string(),
             image: "https://placehold.co/1600x900".to_string(),
-            r#type: Faker.fake(),
-            created_at: created_at(),
+            r#type: rng.gen_range(ContentType::Short as i32..=ContentType::AiGenerated as i32),
+            created_at: Some(created_at(&mut rng)),
             views,
             likes,
             dislikes,
+            version: 0,
+            locale: String::new(),
+            status: ContentStatus::Published as i32,
+            available_from: None,
+            available_until: None,
         }
     }
 
@@ -70,21 +113,56 @@ impl<'a> Tpl<'a> {
     pub fn to_body(&self) -> String {
         format!("Tpl: {:?}", self.0)
     }
+
+    /// a list of links to the contents
+    pub fn to_html(&self) -> String {
+        let items: String = self
+            .0
+            .iter()
+            .map(|c| {
+                format!(
+                    r#"<li><a href="{}">{}</a></li>"#,
+                    escape_html(&c.url),
+                    escape_html(&c.name)
+                )
+            })
+            .collect();
+        format!("<html><body><ul>{}</ul></body></html>", items)
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
 impl MaterializeRequest {
-    pub fn new_with_ids(ids: &[u32]) -> impl Stream<Item = Self> {
-        let reqs: HashSet<_> = ids.iter().map(|id| Self { id: *id }).collect();
+    pub fn new(id: u32, locale: impl Into<String>) -> Self {
+        Self {
+            id,
+            locale: locale.into(),
+            only_available: false,
+        }
+    }
+
+    /// the requests of the distinct ids in no particular order, BatchGet keeps the order
+    pub fn new_with_ids(ids: &[u32], locale: &str) -> impl Stream<Item = Self> {
+        let reqs: HashSet<_> = ids.iter().map(|id| Self::new(*id, locale)).collect();
         stream::iter(reqs)
     }
 }
 
 impl Publisher {
-    pub fn new() -> Self {
+    /// make up a publisher, the same for the same seed and id
+    pub fn materialize(seed: u64, id: u32) -> Self {
+        let mut rng = seeded_rng(seed, id);
         Publisher {
-            id: (10000..2000000).fake(),
-            name: Name().fake(),
+            id,
+            name: Name().fake_with_rng(&mut rng),
             avatar: "https://placehold.co/400x400".to_string(),
+            version: 0,
         }
     }
 }
@@ -109,37 +187,137 @@ impl fmt::Display for UnfinishedContents {
     }
 }
 
-fn before(days: u64) -> DateTime<Utc> {
-    Utc::now().checked_sub_days(Days::new(days)).unwrap()
+/// the rng the fake data of an id is made from
+fn seeded_rng(seed: u64, id: u32) -> StdRng {
+    StdRng::seed_from_u64(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ id as u64)
 }
 
-fn created_at() -> Option<Timestamp> {
-    let date: DateTime<Utc> = DateTimeBetween(before(365), before(0)).fake();
-    Some(Timestamp {
+/// within a year before a fixed date, so it doesn't change over time either
+fn created_at(rng: &mut StdRng) -> Timestamp {
+    let end = Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap();
+    let date: DateTime<Utc> = DateTimeBetween(end - Duration::days(365), end).fake_with_rng(rng);
+    to_ts(date)
+}
+
+fn to_ts(date: DateTime<Utc>) -> Timestamp {
+    Timestamp {
         seconds: date.timestamp(),
         nanos: date.timestamp_subsec_nanos() as i32,
-    })
+    }
+}
+
+fn ts_to_utc(ts: &Timestamp) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(ts.seconds, ts.nanos as _).single()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::AppConfig;
     use anyhow::Result;
 
     #[tokio::test]
     async fn materialize_should_work() -> Result<()> {
-        let config = AppConfig::load()?;
-        let service = MetadataService::new(config);
+        let service = MetadataService::new_fake(42).await?;
+        let stream = tokio_stream::iter(vec![
+            Ok(MaterializeRequest::new(1, "")),
+            Ok(MaterializeRequest::new(2, "")),
+            Ok(MaterializeRequest::new(3, "")),
+        ]);
+
+        let response = service.materialize(stream).await?;
+        let ret = response.into_inner().collect::<Vec<_>>().await;
+        assert_eq!(ret.len(), 3);
+        for (i, content) in ret.into_iter().enumerate() {
+            assert_eq!(content?, Content::materialize(42, i as u32 + 1));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn materialize_should_be_deterministic() {
+        let content = Content::materialize(42, 1);
+        assert_eq!(content, Content::materialize(42, 1));
+        assert_ne!(content, Content::materialize(42, 2));
+        assert_ne!(content, Content::materialize(7, 1));
+        assert_ne!(content.r#type(), ContentType::Unspecified);
+        assert_eq!(Publisher::materialize(42, 1), Publisher::materialize(42, 1));
+    }
+
+    #[test]
+    fn tpl_should_render_the_same_for_the_same_seed() {
+        let render = |seed| {
+            let contents: Vec<_> = (1..=3).map(|id| Content::materialize(seed, id)).collect();
+            Tpl(&contents).to_html()
+        };
+        assert_eq!(render(42), render(42));
+        assert_ne!(render(42), render(7));
+    }
+
+    #[tokio::test]
+    async fn materialize_should_keep_processing_after_invalid_item() -> Result<()> {
+        let service = MetadataService::new_fake(42).await?;
         let stream = tokio_stream::iter(vec![
-            Ok(MaterializeRequest { id: 1 }),
-            Ok(MaterializeRequest { id: 2 }),
-            Ok(MaterializeRequest { id: 3 }),
+            Ok(MaterializeRequest::new(1, "")),
+            Err(Status::invalid_argument("bad request")),
+            Ok(MaterializeRequest::new(3, "")),
         ]);
 
         let response = service.materialize(stream).await?;
         let ret = response.into_inner().collect::<Vec<_>>().await;
         assert_eq!(ret.len(), 3);
+        assert_eq!(ret[0].as_ref().unwrap().id, 1);
+        assert_eq!(ret[1].as_ref().unwrap_err().message(), "bad request");
+        assert_eq!(ret[2].as_ref().unwrap().id, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn materialize_should_stop_when_client_disconnects() -> Result<()> {
+        let service = MetadataService::new_fake(42).await?;
+        let (req_tx, req_rx) = mpsc::channel(CHANNEL_SIZE);
+        let stream = ReceiverStream::new(req_rx).map(Ok);
+
+        let mut response = service.materialize(stream).await?.into_inner();
+        req_tx.send(MaterializeRequest::new(1, "")).await?;
+        let content = response.next().await.unwrap()?;
+        assert_eq!(content.id, 1);
+
+        drop(response);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        // the request stream is dropped along with the task
+        assert!(req_tx.is_closed());
+        assert!(req_tx.send(MaterializeRequest::new(2, "")).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn materialize_should_read_catalog() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+        let stream = tokio_stream::iter(vec![
+            Ok(MaterializeRequest::new(1, "")),
+            Ok(MaterializeRequest::new(1000, "")),
+            Ok(MaterializeRequest::new(1, "")),
+        ]);
+
+        let response = service.materialize(stream).await?;
+        let ret = response.into_inner().collect::<Vec<_>>().await;
+        assert_eq!(ret.len(), 3);
+
+        let content = ret[0].as_ref().unwrap();
+        assert_eq!(content.name, "Sit Lorem Sed");
+        assert_eq!(content.r#type(), ContentType::Short);
+        assert_eq!(content.views, 620176);
+        let publishers: Vec<_> = content.publishers.iter().map(|p| p.id).collect();
+        assert_eq!(publishers, [1, 5, 2]);
+        // the same id gets the same content
+        assert_eq!(ret[2].as_ref().unwrap(), content);
+
+        let e = ret[1].as_ref().unwrap_err();
+        assert_eq!(e.code(), tonic::Code::NotFound);
+        assert_eq!(e.message(), "Content 1000 not found");
 
         Ok(())
     }
@@ -0,0 +1,180 @@
+use tonic::{Response, Status};
+use tracing::info;
+
+use crate::{
+    pb::{
+        CreatePublisherRequest, DeletePublisherRequest, DeletePublisherResponse,
+        GetPublisherRequest, Publisher, UpdatePublisherRequest,
+    },
+    MetadataService, ServiceResult,
+};
+
+use super::{
+    catalog::{internal, read_only, PublisherRow},
+    content::{validate_name, validate_url, version_conflict},
+};
+
+const MAX_NAME_LEN: usize = 64;
+
+impl MetadataService {
+    pub async fn get_publisher(&self, req: GetPublisherRequest) -> ServiceResult<Publisher> {
+        let publisher = self.catalog.get_publisher(req.id).await?;
+        Ok(Response::new(publisher))
+    }
+
+    pub async fn create_publisher(&self, req: CreatePublisherRequest) -> ServiceResult<Publisher> {
+        validate_publisher(&req.name, &req.avatar).map_err(Status::invalid_argument)?;
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+
+        let row: PublisherRow = sqlx::query_as(
+            "INSERT INTO publishers (name, avatar) VALUES ($1, $2) RETURNING id, name, avatar, version",
+        )
+        .bind(&req.name)
+        .bind(&req.avatar)
+        .fetch_one(pool)
+        .await
+        .map_err(internal)?;
+
+        let publisher = Publisher::from(row);
+        info!("Publisher {} created", publisher.id);
+        Ok(Response::new(publisher))
+    }
+
+    pub async fn update_publisher(&self, req: UpdatePublisherRequest) -> ServiceResult<Publisher> {
+        validate_publisher(&req.name, &req.avatar).map_err(Status::invalid_argument)?;
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+
+        let row: Option<PublisherRow> = sqlx::query_as(
+            "UPDATE publishers SET name = $3, avatar = $4, version = version + 1 WHERE id = $1 AND version = $2 RETURNING id, name, avatar, version",
+        )
+        .bind(req.id as i64)
+        .bind(req.version as i64)
+        .bind(&req.name)
+        .bind(&req.avatar)
+        .fetch_optional(pool)
+        .await
+        .map_err(internal)?;
+        let Some(row) = row else {
+            return Err(
+                version_conflict(pool, "publishers", "Publisher", req.id, req.version).await,
+            );
+        };
+
+        // the contents show the publisher's name and avatar
+        let linked: Vec<(i64,)> =
+            sqlx::query_as("SELECT content_id FROM content_publishers WHERE publisher_id = $1")
+                .bind(req.id as i64)
+                .fetch_all(pool)
+                .await
+                .map_err(internal)?;
+        self.cache
+            .invalidate(linked.into_iter().map(|(id,)| id as u32));
+
+        info!("Publisher {} updated", req.id);
+        Ok(Response::new(row.into()))
+    }
+
+    /// delete the publisher, and unlink it from its contents
+    pub async fn delete_publisher(
+        &self,
+        req: DeletePublisherRequest,
+    ) -> ServiceResult<DeletePublisherResponse> {
+        let pool = self.catalog.pool().ok_or_else(read_only)?;
+        let mut tx = pool.begin().await.map_err(internal)?;
+        // the contents lose a publisher, so they change too
+        let linked: Vec<(i64,)> = sqlx::query_as("UPDATE contents SET version = version + 1 WHERE id IN (SELECT content_id FROM content_publishers WHERE publisher_id = $1) RETURNING id")
+            .bind(req.id as i64)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(internal)?;
+        let ret = sqlx::query("DELETE FROM publishers WHERE id = $1")
+            .bind(req.id as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal)?;
+        if ret.rows_affected() == 0 {
+            return Err(Status::not_found(format!("Publisher {} not found", req.id)));
+        }
+        tx.commit().await.map_err(internal)?;
+        self.cache
+            .invalidate(linked.into_iter().map(|(id,)| id as u32));
+
+        info!("Publisher {} deleted", req.id);
+        Ok(Response::new(DeletePublisherResponse { id: req.id }))
+    }
+}
+
+pub(super) fn validate_publisher(name: &str, avatar: &str) -> Result<(), String> {
+    validate_name(name, MAX_NAME_LEN)?;
+    if !avatar.is_empty() {
+        validate_url("avatar", avatar)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::GetContentRequest;
+    use anyhow::Result;
+    use tonic::Code;
+
+    #[tokio::test]
+    async fn publisher_crud_should_work() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+
+        let req = CreatePublisherRequest {
+            name: "Ivan Petrov".to_string(),
+            avatar: "javascript:alert(1)".to_string(),
+        };
+        let e = service.create_publisher(req.clone()).await.unwrap_err();
+        assert_eq!(e.code(), Code::InvalidArgument);
+
+        let req = CreatePublisherRequest {
+            avatar: "https://placehold.co/400x400".to_string(),
+            ..req
+        };
+        let publisher = service.create_publisher(req).await?.into_inner();
+        assert_eq!(publisher.version, 1);
+
+        let req = UpdatePublisherRequest {
+            id: publisher.id,
+            version: publisher.version,
+            name: "Ivan P.".to_string(),
+            avatar: publisher.avatar.clone(),
+        };
+        let updated = service.update_publisher(req.clone()).await?.into_inner();
+        assert_eq!(updated.name, "Ivan P.");
+        assert_eq!(updated.version, 2);
+        let e = service.update_publisher(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::Aborted);
+
+        // deleting a publisher unlinks it, and changes the contents it's linked to
+        let content = service
+            .get_content(GetContentRequest {
+                id: 1,
+                ..Default::default()
+            })
+            .await?
+            .into_inner();
+        service
+            .delete_publisher(DeletePublisherRequest { id: 5 })
+            .await?;
+        let e = service
+            .get_publisher(GetPublisherRequest { id: 5 })
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+        let updated = service
+            .get_content(GetContentRequest {
+                id: 1,
+                ..Default::default()
+            })
+            .await?
+            .into_inner();
+        let publishers: Vec<_> = updated.publishers.iter().map(|p| p.id).collect();
+        assert_eq!(publishers, [1, 2]);
+        assert_eq!(updated.version, content.version + 1);
+        Ok(())
+    }
+}
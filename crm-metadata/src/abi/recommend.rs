@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tonic::{Response, Status};
+use tracing::warn;
+
+use crate::{
+    pb::{
+        Content, ContentType, RelatedRequest, RelatedResponse, TrendingRequest, TrendingResponse,
+    },
+    MetadataService, ServiceResult,
+};
+
+use super::{
+    catalog::{internal, type_name, with_publishers, Catalog, ContentRow, CONTENT_COLUMNS},
+    ts_to_utc,
+};
+
+const DEFAULT_WINDOW_DAYS: u32 = 7;
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+/// engagement per hour since the content is created: a like or dislike weighs as much as
+/// ten views. The counters are lifetime totals, so this only tells the recent contents apart
+const VELOCITY: &str = "(views + 10 * likes - 10 * dislikes)::float8 / GREATEST(EXTRACT(EPOCH FROM now() - created_at) / 3600, 1)";
+/// only the published contents in their availability window are recommended
+const AVAILABLE: &str = "status = 'published' AND (available_from IS NULL OR available_from <= now()) AND (available_until IS NULL OR now() < available_until)";
+/// how many made up contents are looked through for the trending ones
+const FAKE_CATALOG_SIZE: u32 = 10_000;
+/// how much each signal adds to the score of a related content
+const SHARED_PUBLISHER_WEIGHT: f64 = 2.0;
+const SAME_TYPE_WEIGHT: f64 = 1.0;
+const CO_WATCH_WEIGHT: f64 = 3.0;
+/// at most this many co-watched contents are considered
+const MAX_CO_WATCHED: i64 = 100;
+/// at most this many recently watched contents are considered for trending
+const MAX_RECENTLY_WATCHED: i64 = 1000;
+
+impl MetadataService {
+    /// the contents ranked by how many users watched them within the window, then the ones
+    /// created within the window by engagement velocity. Without the user stats only the
+    /// latter are ranked, as the counters of a content have no history to tell when they grew
+    pub async fn trending(&self, req: TrendingRequest) -> ServiceResult<TrendingResponse> {
+        let content_type = ContentType::try_from(req.r#type).map_err(|_| {
+            Status::invalid_argument(format!("Invalid content type: {}", req.r#type))
+        })?;
+        let limit = limit(req.limit);
+        let window_days = match req.window_days {
+            0 => DEFAULT_WINDOW_DAYS,
+            days => days,
+        };
+
+        let pool = match &self.catalog {
+            Catalog::Fake(seed) => {
+                // the made up contents are older than any window, so all of them are ranked
+                let now = Utc::now();
+                let mut contents: Vec<_> = (1..=FAKE_CATALOG_SIZE)
+                    .map(|id| Content::materialize(*seed, id))
+                    .filter(|c| {
+                        (content_type == ContentType::Unspecified || c.r#type() == content_type)
+                            && c.is_available(now)
+                    })
+                    .map(|c| (velocity(&c, now), c))
+                    .collect();
+                contents.sort_by(|(a, c), (b, d)| b.total_cmp(a).then(c.id.cmp(&d.id)));
+                let contents = contents
+                    .into_iter()
+                    .take(limit as usize)
+                    .map(|(_, c)| c)
+                    .collect();
+                return Ok(Response::new(TrendingResponse { contents }));
+            }
+            Catalog::Postgres(pool) => pool,
+        };
+
+        let watched = match &self.stats {
+            Some(stats) => recently_watched(stats, window_days).await,
+            None => HashMap::new(),
+        };
+        let (watched_ids, watchers): (Vec<i64>, Vec<i64>) = watched.into_iter().unzip();
+
+        // the type is only compared when given, so the index on it can be used
+        let type_filter = match content_type {
+            ContentType::Unspecified => "",
+            _ => "AND type = $5::content_type",
+        };
+        let sql = format!(
+            "WITH watched AS (SELECT * FROM unnest($3::bigint[], $4::bigint[]) AS w(id, n))
+            SELECT {} FROM (
+                SELECT c.*, COALESCE(w.n, 0) AS watchers FROM contents c
+                LEFT JOIN watched w ON w.id = c.id
+                WHERE (w.id IS NOT NULL OR created_at >= now() - make_interval(days => $1))
+                {} AND {}
+            ) contents ORDER BY watchers DESC, {} DESC, id LIMIT $2",
+            CONTENT_COLUMNS, type_filter, AVAILABLE, VELOCITY
+        );
+        let mut query = sqlx::query_as(&sql)
+            .bind(window_days as i32)
+            .bind(limit as i64)
+            .bind(watched_ids)
+            .bind(watchers);
+        if content_type != ContentType::Unspecified {
+            query = query.bind(type_name(content_type));
+        }
+        let rows: Vec<ContentRow> = query.fetch_all(pool).await.map_err(internal)?;
+
+        let contents = with_publishers(pool, rows).await?;
+        Ok(Response::new(TrendingResponse { contents }))
+    }
+
+    /// the contents related to the given one by shared publishers, type, and being watched
+    /// by the same users
+    pub async fn related(&self, req: RelatedRequest) -> ServiceResult<RelatedResponse> {
+        let limit = limit(req.limit);
+        let pool = match &self.catalog {
+            Catalog::Fake(seed) => {
+                let contents = (1..=limit + 1)
+                    .filter(|id| *id != req.content_id)
+                    .take(limit as usize)
+                    .map(|id| Content::materialize(*seed, id))
+                    .collect();
+                return Ok(Response::new(RelatedResponse { contents }));
+            }
+            Catalog::Postgres(pool) => pool,
+        };
+        // NOT_FOUND for an unknown content
+        self.catalog.get(req.content_id).await?;
+
+        let co_watched = match &self.stats {
+            Some(stats) => co_watched(stats, req.content_id).await,
+            None => HashMap::new(),
+        };
+        // the most watched together gets the full weight
+        let max = co_watched.values().copied().max().unwrap_or(1) as f64;
+        let (co_ids, co_weights): (Vec<i64>, Vec<f64>) = co_watched
+            .into_iter()
+            .map(|(id, n)| (id, n as f64 / max))
+            .unzip();
+
+        let sql = format!(
+            "WITH target AS (SELECT id, type FROM contents WHERE id = $1),
+            shared AS (
+                SELECT b.content_id AS id, COUNT(*) AS n FROM content_publishers a
+                JOIN content_publishers b ON a.publisher_id = b.publisher_id
+                WHERE a.content_id = $1 AND b.content_id <> $1 GROUP BY b.content_id
+            ),
+            co AS (SELECT * FROM unnest($2::bigint[], $3::float8[]) AS co(id, weight))
+            SELECT {} FROM (
+                SELECT c.*, $4 * COALESCE(s.n, 0)
+                    + CASE WHEN c.type = t.type THEN $5 ELSE 0 END
+                    + $6 * COALESCE(co.weight, 0) AS score
+                FROM contents c CROSS JOIN target t
+                LEFT JOIN shared s ON s.id = c.id
+                LEFT JOIN co ON co.id = c.id
+                WHERE c.id <> t.id AND (s.id IS NOT NULL OR co.id IS NOT NULL OR c.type = t.type)
+                AND {}
+            ) contents ORDER BY score DESC, views DESC, id LIMIT $7",
+            CONTENT_COLUMNS, AVAILABLE
+        );
+        let rows: Vec<ContentRow> = sqlx::query_as(&sql)
+            .bind(req.content_id as i64)
+            .bind(co_ids)
+            .bind(co_weights)
+            .bind(SHARED_PUBLISHER_WEIGHT)
+            .bind(SAME_TYPE_WEIGHT)
+            .bind(CO_WATCH_WEIGHT)
+            .bind(limit as i64)
+            .fetch_all(pool)
+            .await
+            .map_err(internal)?;
+
+        let contents = with_publishers(pool, rows).await?;
+        Ok(Response::new(RelatedResponse { contents }))
+    }
+}
+
+/// the contents in the recent_watched of the users who recently watched the content, along
+/// with how many of them did. Empty if the user stats can't be queried
+async fn co_watched(stats: &PgPool, content_id: u32) -> HashMap<i64, i64> {
+    let Ok(id) = i32::try_from(content_id) else {
+        return HashMap::new();
+    };
+    let ret: Result<Vec<(i64, i64)>, _> = sqlx::query_as(
+        "SELECT w::bigint, COUNT(*) AS n FROM user_stats, unnest(recent_watched) AS w WHERE recent_watched @> ARRAY[$1] AND w <> $1 GROUP BY w ORDER BY n DESC LIMIT $2",
+    )
+    .bind(id)
+    .bind(MAX_CO_WATCHED)
+    .fetch_all(stats)
+    .await;
+    match ret {
+        Ok(rows) => rows.into_iter().collect(),
+        Err(e) => {
+            warn!("Failed to query co-watched contents: {:?}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// the contents in the recent_watched of the users who watched anything within the window,
+/// along with how many of them did. Empty if the user stats can't be queried
+async fn recently_watched(stats: &PgPool, window_days: u32) -> HashMap<i64, i64> {
+    let ret: Result<Vec<(i64, i64)>, _> = sqlx::query_as(
+        "SELECT w::bigint, COUNT(*) AS n FROM user_stats, unnest(recent_watched) AS w WHERE last_watched_at >= now() - make_interval(days => $1) GROUP BY w ORDER BY n DESC LIMIT $2",
+    )
+    .bind(window_days as i32)
+    .bind(MAX_RECENTLY_WATCHED)
+    .fetch_all(stats)
+    .await;
+    match ret {
+        Ok(rows) => rows.into_iter().collect(),
+        Err(e) => {
+            warn!("Failed to query recently watched contents: {:?}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// the same score as [`VELOCITY`], for the made up contents
+fn velocity(content: &Content, now: DateTime<Utc>) -> f64 {
+    let engagement =
+        content.views as f64 + 10.0 * content.likes as f64 - 10.0 * content.dislikes as f64;
+    let created_at = content
+        .created_at
+        .as_ref()
+        .and_then(ts_to_utc)
+        .unwrap_or(now);
+    let hours = (now - created_at).num_seconds() as f64 / 3600.0;
+    engagement / hours.max(1.0)
+}
+
+fn limit(limit: u32) -> u32 {
+    match limit {
+        0 => DEFAULT_LIMIT,
+        limit => limit.min(MAX_LIMIT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abi::ContentCache, MetadataServiceInner};
+    use anyhow::Result;
+    use sqlx::Executor;
+    use std::sync::Arc;
+    use tonic::Code;
+
+    #[tokio::test]
+    async fn trending_should_rank_recent_contents_by_velocity() -> Result<()> {
+        let (tdb, service) = MetadataService::new_for_test().await?;
+        let pool = tdb.get_pool().await;
+        pool.execute(
+            "UPDATE contents SET created_at = now() - interval '2 hours', views = 1000, likes = 100, dislikes = 0, type = 'short' WHERE id = 1;
+            UPDATE contents SET created_at = now() - interval '1 day', views = 10000, likes = 0, dislikes = 0, type = 'movie' WHERE id = 2;
+            UPDATE contents SET created_at = now() - interval '3 days', views = 100, likes = 0, dislikes = 50, type = 'short' WHERE id = 3;
+            UPDATE contents SET created_at = now() - interval '30 days', views = 1000000 WHERE id = 4",
+        )
+        .await?;
+
+        let res = service
+            .trending(TrendingRequest::default())
+            .await?
+            .into_inner();
+        let ids: Vec<_> = res.contents.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+
+        // the unavailable contents are not trending
+        pool.execute(
+            "UPDATE contents SET status = 'draft' WHERE id = 1;
+            UPDATE contents SET available_until = now() - interval '1 hour' WHERE id = 3",
+        )
+        .await?;
+        let res = service
+            .trending(TrendingRequest::default())
+            .await?
+            .into_inner();
+        let ids: Vec<_> = res.contents.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [2]);
+        pool.execute("UPDATE contents SET status = 'published', available_until = NULL")
+            .await?;
+
+        let req = TrendingRequest {
+            r#type: ContentType::Short as i32,
+            window_days: 60,
+            limit: 1,
+        };
+        let res = service.trending(req).await?.into_inner();
+        let ids: Vec<_> = res.contents.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn trending_should_rank_contents_watched_within_window_first() -> Result<()> {
+        let (tdb, _) = MetadataService::new_for_test().await?;
+        let pool = tdb.get_pool().await;
+        pool.execute(
+            "UPDATE contents SET created_at = now() - interval '30 days';
+            UPDATE contents SET created_at = now() - interval '2 hours', views = 1000 WHERE id = 1;
+            CREATE TABLE user_stats (email varchar(128) PRIMARY KEY, last_watched_at timestamptz, recent_watched int[]);
+            INSERT INTO user_stats VALUES
+                ('a@acme.org', now() - interval '1 day', '{4,5}'),
+                ('b@acme.org', now() - interval '2 days', '{4}'),
+                ('c@acme.org', now() - interval '20 days', '{6,6}')",
+        )
+        .await?;
+        let service = MetadataService {
+            inner: Arc::new(MetadataServiceInner {
+                config: crate::AppConfig::load()?,
+                catalog: Catalog::Postgres(pool.clone()),
+                cache: ContentCache::new(&Default::default()),
+                stats: Some(pool),
+            }),
+        };
+
+        let res = service.trending(TrendingRequest::default()).await?;
+        let ids: Vec<_> = res.into_inner().contents.iter().map(|c| c.id).collect();
+        // 6 is watched before the window, and the rest of the old contents not at all
+        assert_eq!(ids, [4, 5, 1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fake_trending_should_filter_by_type_and_rank_by_velocity() -> Result<()> {
+        let service = MetadataService::new_fake(42).await?;
+        let req = TrendingRequest {
+            r#type: ContentType::Movie as i32,
+            limit: 5,
+            ..Default::default()
+        };
+        let contents = service.trending(req).await?.into_inner().contents;
+        assert_eq!(contents.len(), 5);
+        let now = Utc::now();
+        assert!(contents
+            .iter()
+            .all(|c| c.r#type() == ContentType::Movie && c.is_available(now)));
+        assert!(contents
+            .windows(2)
+            .all(|w| velocity(&w[0], now) >= velocity(&w[1], now)));
+
+        // nothing left out ranks higher than the last one returned
+        let last = velocity(&contents[4], now);
+        let higher = (1..=FAKE_CATALOG_SIZE)
+            .map(|id| Content::materialize(42, id))
+            .filter(|c| c.r#type() == ContentType::Movie && c.is_available(now))
+            .filter(|c| velocity(c, now) > last)
+            .count();
+        assert!(higher <= 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn related_should_use_co_watch() -> Result<()> {
+        let (tdb, service) = MetadataService::new_for_test().await?;
+        let req = RelatedRequest {
+            content_id: 1,
+            limit: 100,
+        };
+        let without = service.related(req.clone()).await?.into_inner().contents;
+        assert!(without.iter().all(|c| c.id != 1));
+        // every related content shares a publisher or the type
+        let content = service.catalog.get(1).await?;
+        for related in without.iter() {
+            let shared = related
+                .publishers
+                .iter()
+                .any(|p| content.publishers.iter().any(|q| q.id == p.id));
+            assert!(shared || related.r#type == content.r#type);
+        }
+
+        // the user stats live in the same test db
+        let pool = tdb.get_pool().await;
+        pool.execute(
+            "CREATE TABLE user_stats (email varchar(128) PRIMARY KEY, recent_watched int[]);
+            INSERT INTO user_stats VALUES ('a@acme.org', '{1,20}'), ('b@acme.org', '{20,1,19}'), ('c@acme.org', '{19}')",
+        )
+        .await?;
+        let service = MetadataService {
+            inner: Arc::new(MetadataServiceInner {
+                config: crate::AppConfig::load()?,
+                catalog: Catalog::Postgres(pool.clone()),
+                cache: ContentCache::new(&Default::default()),
+                stats: Some(pool),
+            }),
+        };
+        let with = service.related(req).await?.into_inner().contents;
+        let rank = |contents: &[Content], id| contents.iter().position(|c| c.id == id);
+        let rank_20 = rank(&with, 20).unwrap();
+        assert!(!matches!(rank(&without, 20), Some(r) if r <= rank_20));
+        assert!(rank(&with, 19).is_some());
+
+        let req = RelatedRequest {
+            content_id: 1000,
+            limit: 0,
+        };
+        let e = service.related(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::NotFound);
+        Ok(())
+    }
+}
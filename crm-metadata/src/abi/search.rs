@@ -0,0 +1,233 @@
+use sqlx::{Postgres, QueryBuilder};
+use tonic::{Response, Status};
+use tracing::debug;
+
+use crate::{
+    pb::{ContentType, SearchRequest, SearchResponse, SearchSort},
+    MetadataService, ServiceResult,
+};
+
+use super::{
+    catalog::{internal, type_name, with_publishers, ContentRow, CONTENT_COLUMNS},
+    ts_to_utc,
+};
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+impl MetadataService {
+    /// find the contents matching the query and filters, a page at a time
+    pub async fn search(&self, req: SearchRequest) -> ServiceResult<SearchResponse> {
+        let Some(pool) = self.catalog.pool() else {
+            return Err(Status::failed_precondition(
+                "The fake catalog can't be searched",
+            ));
+        };
+        validate_search(&req).map_err(Status::invalid_argument)?;
+        let offset: i64 = match req.page_token.as_str() {
+            "" => 0,
+            token => token
+                .parse()
+                .map_err(|_| Status::invalid_argument("Invalid page_token"))?,
+        };
+        let page_size = match req.page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM contents WHERE TRUE");
+        push_filters(&mut count, &req);
+        let (total,): (i64,) = count
+            .build_query_as()
+            .fetch_one(pool)
+            .await
+            .map_err(internal)?;
+
+        let mut query = QueryBuilder::new(format!(
+            "SELECT {} FROM contents WHERE TRUE",
+            CONTENT_COLUMNS
+        ));
+        push_filters(&mut query, &req);
+        push_order(&mut query, &req);
+        query.push(" LIMIT ").push_bind(page_size as i64);
+        query.push(" OFFSET ").push_bind(offset);
+        debug!("Search SQL: {}", query.sql());
+
+        let rows: Vec<ContentRow> = query
+            .build_query_as()
+            .fetch_all(pool)
+            .await
+            .map_err(internal)?;
+        let next = offset + rows.len() as i64;
+        let next_page_token = if next < total {
+            next.to_string()
+        } else {
+            String::new()
+        };
+
+        let contents = with_publishers(pool, rows).await?;
+        Ok(Response::new(SearchResponse {
+            contents,
+            next_page_token,
+            total: total as u64,
+        }))
+    }
+}
+
+fn validate_search(req: &SearchRequest) -> Result<(), String> {
+    for t in req.types.iter() {
+        if ContentType::try_from(*t).is_err() {
+            return Err(format!("Invalid content type: {}", t));
+        }
+    }
+    if SearchSort::try_from(req.sort).is_err() {
+        return Err(format!("Invalid sort: {}", req.sort));
+    }
+    let after = req.created_after.as_ref().map(ts_to_utc);
+    let before = req.created_before.as_ref().map(ts_to_utc);
+    if matches!(after, Some(None)) || matches!(before, Some(None)) {
+        return Err("Invalid created_at range".to_string());
+    }
+    if let (Some(after), Some(before)) = (after.flatten(), before.flatten()) {
+        if after > before {
+            return Err("created_after is later than created_before".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn push_filters(query: &mut QueryBuilder<'_, Postgres>, req: &SearchRequest) {
+    if !req.query.trim().is_empty() {
+        query
+            .push(" AND search @@ websearch_to_tsquery('english', ")
+            .push_bind(req.query.clone())
+            .push(")");
+    }
+    if !req.types.is_empty() {
+        let types: Vec<String> = req.types().map(type_name).collect();
+        query
+            .push(" AND type = ANY(")
+            .push_bind(types)
+            .push("::content_type[])");
+    }
+    if !req.publisher_ids.is_empty() {
+        let ids: Vec<i64> = req.publisher_ids.iter().map(|id| *id as i64).collect();
+        query
+            .push(" AND EXISTS (SELECT 1 FROM content_publishers cp WHERE cp.content_id = contents.id AND cp.publisher_id = ANY(")
+            .push_bind(ids)
+            .push("))");
+    }
+    if let Some(after) = req.created_after.as_ref().and_then(ts_to_utc) {
+        query.push(" AND created_at >= ").push_bind(after);
+    }
+    if let Some(before) = req.created_before.as_ref().and_then(ts_to_utc) {
+        query.push(" AND created_at <= ").push_bind(before);
+    }
+}
+
+fn push_order(query: &mut QueryBuilder<'_, Postgres>, req: &SearchRequest) {
+    let dir = if req.ascending { "ASC" } else { "DESC" };
+    let has_query = !req.query.trim().is_empty();
+    query.push(" ORDER BY ");
+    match req.sort() {
+        SearchSort::Views => {
+            query.push(format!("views {}", dir));
+        }
+        SearchSort::Likes => {
+            query.push(format!("likes {}", dir));
+        }
+        SearchSort::LikeRatio => {
+            // contents without any vote go last either way
+            query.push(format!(
+                "likes::float8 / NULLIF(likes + dislikes, 0) {} NULLS LAST",
+                dir
+            ));
+        }
+        SearchSort::CreatedAt => {
+            query.push(format!("created_at {}", dir));
+        }
+        SearchSort::Unspecified if has_query => {
+            query
+                .push("ts_rank(search, websearch_to_tsquery('english', ")
+                .push_bind(req.query.clone())
+                .push(format!(")) {}", dir));
+        }
+        SearchSort::Unspecified => {
+            query.push(format!("id {}", dir));
+            return;
+        }
+    }
+    // a stable order for the pagination
+    query.push(", id ASC");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::collections::HashSet;
+    use tonic::Code;
+
+    #[tokio::test]
+    async fn search_should_match_query_and_filters() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+
+        let req = SearchRequest {
+            query: "lorem".to_string(),
+            ..Default::default()
+        };
+        let res = service.search(req).await?.into_inner();
+        assert!(res.total > 0);
+        for content in res.contents.iter() {
+            let text = format!("{} {}", content.name, content.description).to_lowercase();
+            assert!(text.contains("lorem"), "{}", text);
+        }
+
+        let req = SearchRequest {
+            types: vec![ContentType::Short as i32],
+            publisher_ids: vec![1, 2],
+            sort: SearchSort::Views as i32,
+            ..Default::default()
+        };
+        let res = service.search(req).await?.into_inner();
+        assert!(!res.contents.is_empty());
+        for content in res.contents.iter() {
+            assert_eq!(content.r#type(), ContentType::Short);
+            assert!(content.publishers.iter().any(|p| p.id == 1 || p.id == 2));
+        }
+        let views: Vec<_> = res.contents.iter().map(|c| c.views).collect();
+        assert!(views.windows(2).all(|w| w[0] >= w[1]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn search_should_paginate() -> Result<()> {
+        let (_tdb, service) = MetadataService::new_for_test().await?;
+
+        let mut req = SearchRequest {
+            sort: SearchSort::LikeRatio as i32,
+            ascending: true,
+            page_size: 7,
+            ..Default::default()
+        };
+        let mut pages = vec![];
+        let mut ids = HashSet::new();
+        loop {
+            let res = service.search(req.clone()).await?.into_inner();
+            assert_eq!(res.total, 20);
+            pages.push(res.contents.len());
+            ids.extend(res.contents.iter().map(|c| c.id));
+            if res.next_page_token.is_empty() {
+                break;
+            }
+            req.page_token = res.next_page_token;
+        }
+        assert_eq!(pages, [7, 7, 6]);
+        assert_eq!(ids.len(), 20);
+
+        req.page_token = "next".to_string();
+        let e = service.search(req).await.unwrap_err();
+        assert_eq!(e.code(), Code::InvalidArgument);
+        Ok(())
+    }
+}
@@ -7,11 +7,61 @@ use serde::{Deserialize, Serialize};
 pub struct AppConfig {
     pub server: ServerConfig,
     pub auth: AuthConfig,
+    pub catalog: CatalogConfig,
+    #[serde(default)]
+    pub related: RelatedConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub port: u16,
+    /// port of the prometheus metrics endpoint, disabled if not set
+    pub metrics_port: Option<u16>,
+    /// how long (in seconds) to drain the in-flight requests on shutdown
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
+    /// max ids of a batch get
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+}
+
+/// where the contents are read from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CatalogConfig {
+    /// made up contents, the same content for the same seed and id
+    Fake {
+        #[serde(default)]
+        seed: u64,
+    },
+    /// contents and publishers stored in postgres
+    Postgres { db_url: String },
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RelatedConfig {
+    /// the user stats db to find the contents watched together, not used if not set
+    pub stats_db_url: Option<String>,
+}
+
+/// the contents kept in memory, so the same ones aren't read again and again
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// how many contents to keep, the cache is disabled if 0
+    pub capacity: usize,
+    /// how long (in seconds) a content is served from the cache
+    pub ttl: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10000,
+            ttl: 60,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,3 +95,11 @@ impl AppConfig {
         bail!("Failed to load configuration");
     }
 }
+
+fn default_shutdown_timeout() -> u64 {
+    30
+}
+
+fn default_max_batch_size() -> usize {
+    1000
+}
@@ -1,5 +1,8 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use crm_metadata::{AppConfig, MetadataService};
+use metrics_exporter_prometheus::PrometheusBuilder;
 use tonic::transport::Server;
 use tracing::{info, level_filters::LevelFilter};
 use tracing_subscriber::{fmt::Layer, layer::SubscriberExt, util::SubscriberInitExt, Layer as _};
@@ -12,8 +15,27 @@ async fn main() -> Result<()> {
     let config = AppConfig::load().expect("Failed to load config");
     let addr = config.server.port;
     let addr = format!("[::1]:{}", addr).parse().unwrap();
+    let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout);
     info!("Metadata service listening on {}", addr);
-    let svc = MetadataService::new(config).into_server();
-    Server::builder().add_service(svc).serve(addr).await?;
+
+    if let Some(port) = config.server.metrics_port {
+        let metrics_addr: std::net::SocketAddr = format!("[::1]:{}", port).parse()?;
+        PrometheusBuilder::new()
+            .with_http_listener(metrics_addr)
+            .install()?;
+        info!("Metrics exposed on http://{}/metrics", metrics_addr);
+    }
+
+    let svc = MetadataService::new(config).await.into_server();
+    // on shutdown, stop accepting requests, and let the in-flight streams finish
+    crm_shutdown::serve(
+        |stop| {
+            Server::builder()
+                .add_service(svc)
+                .serve_with_shutdown(addr, stop)
+        },
+        shutdown_timeout,
+    )
+    .await?;
     Ok(())
 }
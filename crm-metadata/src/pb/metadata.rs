@@ -24,6 +24,21 @@ pub struct Content {
     pub likes: u64,
     #[prost(uint64, tag = "11")]
     pub dislikes: u64,
+    /// bumped on every change, updates must give the version they are based on
+    #[prost(uint64, tag = "12")]
+    pub version: u64,
+    /// the locale of the name and description, empty for the default ones
+    #[prost(string, tag = "13")]
+    pub locale: ::prost::alloc::string::String,
+    /// contents are created published
+    #[prost(enumeration = "ContentStatus", tag = "14")]
+    pub status: i32,
+    /// a published content is available from available_from until available_until, either
+    /// end is open if not set
+    #[prost(message, optional, tag = "15")]
+    pub available_from: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "16")]
+    pub available_until: ::core::option::Option<::prost_types::Timestamp>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -34,6 +49,8 @@ pub struct Publisher {
     pub name: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
     pub avatar: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub version: u64,
 }
 #[derive(Eq, Hash)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -41,6 +58,54 @@ pub struct Publisher {
 pub struct MaterializeRequest {
     #[prost(uint32, tag = "1")]
     pub id: u32,
+    /// the locale to get the name and description in, e.g. zh-CN falls back to zh, and then
+    /// to the default ones
+    #[prost(string, tag = "2")]
+    pub locale: ::prost::alloc::string::String,
+    /// FAILED_PRECONDITION instead of the content if it's not available now
+    #[prost(bool, tag = "3")]
+    pub only_available: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchGetRequest {
+    /// at most server.max_batch_size of the config (1000 by default), the same id can be given
+    /// more than once
+    #[prost(uint32, repeated, tag = "1")]
+    pub ids: ::prost::alloc::vec::Vec<u32>,
+    /// same as the locale and only_available of MaterializeRequest
+    #[prost(string, tag = "2")]
+    pub locale: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub only_available: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchGetResponse {
+    /// one for each of the ids, in the same order
+    #[prost(message, repeated, tag = "1")]
+    pub items: ::prost::alloc::vec::Vec<BatchGetItem>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchGetItem {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(oneof = "batch_get_item::Item", tags = "2, 3")]
+    pub item: ::core::option::Option<batch_get_item::Item>,
+}
+/// Nested message and enum types in `BatchGetItem`.
+pub mod batch_get_item {
+    #[allow(clippy::large_enum_variant)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Item {
+        #[prost(message, tag = "2")]
+        Content(super::Content),
+        /// why there's no content, e.g. it's not found or not available
+        #[prost(string, tag = "3")]
+        Missing(::prost::alloc::string::String),
+    }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -52,6 +117,280 @@ pub struct UnfinishedContents {
     #[prost(int64, repeated, tag = "3")]
     pub started_but_not_finished: ::prost::alloc::vec::Vec<i64>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContentRequest {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    /// same as the locale of MaterializeRequest
+    #[prost(string, tag = "2")]
+    pub locale: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateContentRequest {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub image: ::prost::alloc::string::String,
+    #[prost(enumeration = "ContentType", tag = "5")]
+    pub r#type: i32,
+    #[prost(uint32, repeated, tag = "6")]
+    pub publisher_ids: ::prost::alloc::vec::Vec<u32>,
+}
+/// replaces the editable fields of the content, if it's still at the given version
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateContentRequest {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(uint64, tag = "2")]
+    pub version: u64,
+    #[prost(string, tag = "3")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub image: ::prost::alloc::string::String,
+    #[prost(enumeration = "ContentType", tag = "7")]
+    pub r#type: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteContentRequest {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteContentResponse {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetPublisherRequest {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreatePublisherRequest {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub avatar: ::prost::alloc::string::String,
+}
+/// replaces the editable fields of the publisher, if it's still at the given version
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdatePublisherRequest {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(uint64, tag = "2")]
+    pub version: u64,
+    #[prost(string, tag = "3")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub avatar: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeletePublisherRequest {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeletePublisherResponse {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LinkPublisherRequest {
+    #[prost(uint32, tag = "1")]
+    pub content_id: u32,
+    #[prost(uint32, tag = "2")]
+    pub publisher_id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnlinkPublisherRequest {
+    #[prost(uint32, tag = "1")]
+    pub content_id: u32,
+    #[prost(uint32, tag = "2")]
+    pub publisher_id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchRequest {
+    /// full-text search on name and description, all contents if empty
+    #[prost(string, tag = "1")]
+    pub query: ::prost::alloc::string::String,
+    /// any of the types, all types if empty
+    #[prost(enumeration = "ContentType", repeated, tag = "2")]
+    pub types: ::prost::alloc::vec::Vec<i32>,
+    /// linked to any of the publishers, all publishers if empty
+    #[prost(uint32, repeated, tag = "3")]
+    pub publisher_ids: ::prost::alloc::vec::Vec<u32>,
+    #[prost(message, optional, tag = "4")]
+    pub created_after: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub created_before: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(enumeration = "SearchSort", tag = "6")]
+    pub sort: i32,
+    /// descending if not set
+    #[prost(bool, tag = "7")]
+    pub ascending: bool,
+    /// 20 if not set, at most 100
+    #[prost(uint32, tag = "8")]
+    pub page_size: u32,
+    /// next_page_token of the previous page, empty for the first page
+    #[prost(string, tag = "9")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub contents: ::prost::alloc::vec::Vec<Content>,
+    /// empty on the last page
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+    /// contents matching the request, across all pages
+    #[prost(uint64, tag = "3")]
+    pub total: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TrendingRequest {
+    /// all types if not set
+    #[prost(enumeration = "ContentType", tag = "1")]
+    pub r#type: i32,
+    /// only contents watched or created in the last window_days are ranked, 7 if not set
+    #[prost(uint32, tag = "2")]
+    pub window_days: u32,
+    /// 10 if not set, at most 100
+    #[prost(uint32, tag = "3")]
+    pub limit: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TrendingResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub contents: ::prost::alloc::vec::Vec<Content>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RelatedRequest {
+    #[prost(uint32, tag = "1")]
+    pub content_id: u32,
+    /// 10 if not set, at most 100
+    #[prost(uint32, tag = "2")]
+    pub limit: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RelatedResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub contents: ::prost::alloc::vec::Vec<Content>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportRequest {
+    #[prost(enumeration = "CatalogFormat", tag = "1")]
+    pub format: i32,
+    #[prost(enumeration = "CatalogRecord", tag = "2")]
+    pub record: i32,
+    /// the publishers of the contents must be imported first. The translations of a content
+    /// are replaced if given, and kept otherwise
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportReport {
+    #[prost(uint32, tag = "1")]
+    pub imported: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub rejected: ::prost::alloc::vec::Vec<RejectedRow>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RejectedRow {
+    /// 1-based, counting the csv header
+    #[prost(uint32, tag = "1")]
+    pub line: u32,
+    /// 0 if the row can't be parsed
+    #[prost(uint32, tag = "2")]
+    pub id: u32,
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportRequest {
+    #[prost(enumeration = "CatalogFormat", tag = "1")]
+    pub format: i32,
+    #[prost(enumeration = "CatalogRecord", tag = "2")]
+    pub record: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+/// the name and description of a content in a locale
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Translation {
+    #[prost(string, tag = "1")]
+    pub locale: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub description: ::prost::alloc::string::String,
+}
+/// add the translation of the content, or replace the one in the same locale
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutTranslationRequest {
+    #[prost(uint32, tag = "1")]
+    pub content_id: u32,
+    #[prost(message, optional, tag = "2")]
+    pub translation: ::core::option::Option<Translation>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteTranslationRequest {
+    #[prost(uint32, tag = "1")]
+    pub content_id: u32,
+    #[prost(string, tag = "2")]
+    pub locale: ::prost::alloc::string::String,
+}
+/// sets the status and availability window of the content, if it's still at the given version
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetAvailabilityRequest {
+    #[prost(uint32, tag = "1")]
+    pub content_id: u32,
+    #[prost(enumeration = "ContentStatus", tag = "2")]
+    pub status: i32,
+    #[prost(message, optional, tag = "3")]
+    pub available_from: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "4")]
+    pub available_until: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(uint64, tag = "5")]
+    pub version: u64,
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum ContentType {
@@ -87,6 +426,136 @@ impl ContentType {
         }
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ContentStatus {
+    Unspecified = 0,
+    Draft = 1,
+    Published = 2,
+    /// taken down
+    Archived = 3,
+}
+impl ContentStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ContentStatus::Unspecified => "CONTENT_STATUS_UNSPECIFIED",
+            ContentStatus::Draft => "CONTENT_STATUS_DRAFT",
+            ContentStatus::Published => "CONTENT_STATUS_PUBLISHED",
+            ContentStatus::Archived => "CONTENT_STATUS_ARCHIVED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CONTENT_STATUS_UNSPECIFIED" => Some(Self::Unspecified),
+            "CONTENT_STATUS_DRAFT" => Some(Self::Draft),
+            "CONTENT_STATUS_PUBLISHED" => Some(Self::Published),
+            "CONTENT_STATUS_ARCHIVED" => Some(Self::Archived),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SearchSort {
+    /// by relevance to the query, or by id without a query
+    Unspecified = 0,
+    Views = 1,
+    Likes = 2,
+    /// likes / (likes + dislikes)
+    LikeRatio = 3,
+    CreatedAt = 4,
+}
+impl SearchSort {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            SearchSort::Unspecified => "SEARCH_SORT_UNSPECIFIED",
+            SearchSort::Views => "SEARCH_SORT_VIEWS",
+            SearchSort::Likes => "SEARCH_SORT_LIKES",
+            SearchSort::LikeRatio => "SEARCH_SORT_LIKE_RATIO",
+            SearchSort::CreatedAt => "SEARCH_SORT_CREATED_AT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SEARCH_SORT_UNSPECIFIED" => Some(Self::Unspecified),
+            "SEARCH_SORT_VIEWS" => Some(Self::Views),
+            "SEARCH_SORT_LIKES" => Some(Self::Likes),
+            "SEARCH_SORT_LIKE_RATIO" => Some(Self::LikeRatio),
+            "SEARCH_SORT_CREATED_AT" => Some(Self::CreatedAt),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CatalogFormat {
+    Unspecified = 0,
+    /// one json object per line
+    Jsonl = 1,
+    /// with a header row, publisher_ids are separated by ';' and translations is a json array
+    Csv = 2,
+}
+impl CatalogFormat {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CatalogFormat::Unspecified => "CATALOG_FORMAT_UNSPECIFIED",
+            CatalogFormat::Jsonl => "CATALOG_FORMAT_JSONL",
+            CatalogFormat::Csv => "CATALOG_FORMAT_CSV",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CATALOG_FORMAT_UNSPECIFIED" => Some(Self::Unspecified),
+            "CATALOG_FORMAT_JSONL" => Some(Self::Jsonl),
+            "CATALOG_FORMAT_CSV" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CatalogRecord {
+    Unspecified = 0,
+    Content = 1,
+    Publisher = 2,
+}
+impl CatalogRecord {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CatalogRecord::Unspecified => "CATALOG_RECORD_UNSPECIFIED",
+            CatalogRecord::Content => "CATALOG_RECORD_CONTENT",
+            CatalogRecord::Publisher => "CATALOG_RECORD_PUBLISHER",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CATALOG_RECORD_UNSPECIFIED" => Some(Self::Unspecified),
+            "CATALOG_RECORD_CONTENT" => Some(Self::Content),
+            "CATALOG_RECORD_PUBLISHER" => Some(Self::Publisher),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod metadata_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -191,30 +660,447 @@ pub mod metadata_client {
                 .insert(GrpcMethod::new("metadata.Metadata", "Materialize"));
             self.inner.streaming(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod metadata_server {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with MetadataServer.
-    #[async_trait]
-    pub trait Metadata: Send + Sync + 'static {
-        /// Server streaming response type for the Materialize method.
-        type MaterializeStream: tonic::codegen::tokio_stream::Stream<
-                Item = std::result::Result<super::Content, tonic::Status>,
-            > + Send
-            + 'static;
-        async fn materialize(
-            &self,
-            request: tonic::Request<tonic::Streaming<super::MaterializeRequest>>,
-        ) -> std::result::Result<tonic::Response<Self::MaterializeStream>, tonic::Status>;
-    }
-    #[derive(Debug)]
-    pub struct MetadataServer<T: Metadata> {
-        inner: _Inner<T>,
-        accept_compression_encodings: EnabledCompressionEncodings,
-        send_compression_encodings: EnabledCompressionEncodings,
+        /// the contents of a few ids at once, in the order of the ids
+        pub async fn batch_get(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BatchGetRequest>,
+        ) -> std::result::Result<tonic::Response<super::BatchGetResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/BatchGet");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "BatchGet"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_content(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetContentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/GetContent");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "GetContent"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_content(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateContentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/CreateContent");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "CreateContent"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_content(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateContentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/UpdateContent");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "UpdateContent"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn delete_content(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteContentRequest>,
+        ) -> std::result::Result<tonic::Response<super::DeleteContentResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/DeleteContent");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "DeleteContent"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_publisher(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetPublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Publisher>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/GetPublisher");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "GetPublisher"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_publisher(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreatePublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Publisher>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/CreatePublisher");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "CreatePublisher"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_publisher(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdatePublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Publisher>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/UpdatePublisher");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "UpdatePublisher"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn delete_publisher(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeletePublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::DeletePublisherResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/DeletePublisher");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "DeletePublisher"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// add the publisher to the end of the content's publishers
+        pub async fn link_publisher(
+            &mut self,
+            request: impl tonic::IntoRequest<super::LinkPublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/LinkPublisher");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "LinkPublisher"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn unlink_publisher(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UnlinkPublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/UnlinkPublisher");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "UnlinkPublisher"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// the content in the locale of the translation is returned
+        pub async fn put_translation(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PutTranslationRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/PutTranslation");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "PutTranslation"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn delete_translation(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteTranslationRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/DeleteTranslation");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "DeleteTranslation"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// set the status and the availability window of the content
+        pub async fn set_availability(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetAvailabilityRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/SetAvailability");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "SetAvailability"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn search(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SearchRequest>,
+        ) -> std::result::Result<tonic::Response<super::SearchResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/Search");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "Search"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// the contents watched by the most users within the window, then the ones created within
+        /// it with the most engagement per hour
+        pub async fn trending(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TrendingRequest>,
+        ) -> std::result::Result<tonic::Response<super::TrendingResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/Trending");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "Trending"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// contents sharing publishers or type with the content, or watched by the same users
+        pub async fn related(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RelatedRequest>,
+        ) -> std::result::Result<tonic::Response<super::RelatedResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/Related");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "Related"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// upsert contents or publishers by id, the rows that can't be imported are reported
+        pub async fn import(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImportRequest>,
+        ) -> std::result::Result<tonic::Response<super::ImportReport>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/Import");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "Import"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn export(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExportRequest>,
+        ) -> std::result::Result<tonic::Response<super::ExportResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/metadata.Metadata/Export");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("metadata.Metadata", "Export"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod metadata_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with MetadataServer.
+    #[async_trait]
+    pub trait Metadata: Send + Sync + 'static {
+        /// Server streaming response type for the Materialize method.
+        type MaterializeStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::Content, tonic::Status>,
+            > + Send
+            + 'static;
+        async fn materialize(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::MaterializeRequest>>,
+        ) -> std::result::Result<tonic::Response<Self::MaterializeStream>, tonic::Status>;
+        /// the contents of a few ids at once, in the order of the ids
+        async fn batch_get(
+            &self,
+            request: tonic::Request<super::BatchGetRequest>,
+        ) -> std::result::Result<tonic::Response<super::BatchGetResponse>, tonic::Status>;
+        async fn get_content(
+            &self,
+            request: tonic::Request<super::GetContentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status>;
+        async fn create_content(
+            &self,
+            request: tonic::Request<super::CreateContentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status>;
+        async fn update_content(
+            &self,
+            request: tonic::Request<super::UpdateContentRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status>;
+        async fn delete_content(
+            &self,
+            request: tonic::Request<super::DeleteContentRequest>,
+        ) -> std::result::Result<tonic::Response<super::DeleteContentResponse>, tonic::Status>;
+        async fn get_publisher(
+            &self,
+            request: tonic::Request<super::GetPublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Publisher>, tonic::Status>;
+        async fn create_publisher(
+            &self,
+            request: tonic::Request<super::CreatePublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Publisher>, tonic::Status>;
+        async fn update_publisher(
+            &self,
+            request: tonic::Request<super::UpdatePublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Publisher>, tonic::Status>;
+        async fn delete_publisher(
+            &self,
+            request: tonic::Request<super::DeletePublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::DeletePublisherResponse>, tonic::Status>;
+        /// add the publisher to the end of the content's publishers
+        async fn link_publisher(
+            &self,
+            request: tonic::Request<super::LinkPublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status>;
+        async fn unlink_publisher(
+            &self,
+            request: tonic::Request<super::UnlinkPublisherRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status>;
+        /// the content in the locale of the translation is returned
+        async fn put_translation(
+            &self,
+            request: tonic::Request<super::PutTranslationRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status>;
+        async fn delete_translation(
+            &self,
+            request: tonic::Request<super::DeleteTranslationRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status>;
+        /// set the status and the availability window of the content
+        async fn set_availability(
+            &self,
+            request: tonic::Request<super::SetAvailabilityRequest>,
+        ) -> std::result::Result<tonic::Response<super::Content>, tonic::Status>;
+        async fn search(
+            &self,
+            request: tonic::Request<super::SearchRequest>,
+        ) -> std::result::Result<tonic::Response<super::SearchResponse>, tonic::Status>;
+        /// the contents watched by the most users within the window, then the ones created within
+        /// it with the most engagement per hour
+        async fn trending(
+            &self,
+            request: tonic::Request<super::TrendingRequest>,
+        ) -> std::result::Result<tonic::Response<super::TrendingResponse>, tonic::Status>;
+        /// contents sharing publishers or type with the content, or watched by the same users
+        async fn related(
+            &self,
+            request: tonic::Request<super::RelatedRequest>,
+        ) -> std::result::Result<tonic::Response<super::RelatedResponse>, tonic::Status>;
+        /// upsert contents or publishers by id, the rows that can't be imported are reported
+        async fn import(
+            &self,
+            request: tonic::Request<super::ImportRequest>,
+        ) -> std::result::Result<tonic::Response<super::ImportReport>, tonic::Status>;
+        async fn export(
+            &self,
+            request: tonic::Request<super::ExportRequest>,
+        ) -> std::result::Result<tonic::Response<super::ExportResponse>, tonic::Status>;
+    }
+    #[derive(Debug)]
+    pub struct MetadataServer<T: Metadata> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
         max_decoding_message_size: Option<usize>,
         max_encoding_message_size: Option<usize>,
     }
@@ -327,6 +1213,770 @@ pub mod metadata_server {
                     };
                     Box::pin(fut)
                 }
+                "/metadata.Metadata/BatchGet" => {
+                    #[allow(non_camel_case_types)]
+                    struct BatchGetSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::BatchGetRequest> for BatchGetSvc<T> {
+                        type Response = super::BatchGetResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BatchGetRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Metadata>::batch_get(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = BatchGetSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/GetContent" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetContentSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::GetContentRequest> for GetContentSvc<T> {
+                        type Response = super::Content;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetContentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Metadata>::get_content(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetContentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/CreateContent" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateContentSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::CreateContentRequest> for CreateContentSvc<T> {
+                        type Response = super::Content;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateContentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::create_content(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateContentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/UpdateContent" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateContentSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::UpdateContentRequest> for UpdateContentSvc<T> {
+                        type Response = super::Content;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateContentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::update_content(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateContentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/DeleteContent" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteContentSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::DeleteContentRequest> for DeleteContentSvc<T> {
+                        type Response = super::DeleteContentResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteContentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::delete_content(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteContentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/GetPublisher" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetPublisherSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::GetPublisherRequest> for GetPublisherSvc<T> {
+                        type Response = super::Publisher;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetPublisherRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::get_publisher(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetPublisherSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/CreatePublisher" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreatePublisherSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::CreatePublisherRequest>
+                        for CreatePublisherSvc<T>
+                    {
+                        type Response = super::Publisher;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreatePublisherRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::create_publisher(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreatePublisherSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/UpdatePublisher" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdatePublisherSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::UpdatePublisherRequest>
+                        for UpdatePublisherSvc<T>
+                    {
+                        type Response = super::Publisher;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdatePublisherRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::update_publisher(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdatePublisherSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/DeletePublisher" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeletePublisherSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::DeletePublisherRequest>
+                        for DeletePublisherSvc<T>
+                    {
+                        type Response = super::DeletePublisherResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeletePublisherRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::delete_publisher(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeletePublisherSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/LinkPublisher" => {
+                    #[allow(non_camel_case_types)]
+                    struct LinkPublisherSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::LinkPublisherRequest> for LinkPublisherSvc<T> {
+                        type Response = super::Content;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LinkPublisherRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::link_publisher(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = LinkPublisherSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/UnlinkPublisher" => {
+                    #[allow(non_camel_case_types)]
+                    struct UnlinkPublisherSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::UnlinkPublisherRequest>
+                        for UnlinkPublisherSvc<T>
+                    {
+                        type Response = super::Content;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UnlinkPublisherRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::unlink_publisher(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UnlinkPublisherSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/PutTranslation" => {
+                    #[allow(non_camel_case_types)]
+                    struct PutTranslationSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::PutTranslationRequest>
+                        for PutTranslationSvc<T>
+                    {
+                        type Response = super::Content;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PutTranslationRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::put_translation(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PutTranslationSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/DeleteTranslation" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteTranslationSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::DeleteTranslationRequest>
+                        for DeleteTranslationSvc<T>
+                    {
+                        type Response = super::Content;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteTranslationRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::delete_translation(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteTranslationSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/SetAvailability" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetAvailabilitySvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::SetAvailabilityRequest>
+                        for SetAvailabilitySvc<T>
+                    {
+                        type Response = super::Content;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetAvailabilityRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Metadata>::set_availability(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetAvailabilitySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/Search" => {
+                    #[allow(non_camel_case_types)]
+                    struct SearchSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::SearchRequest> for SearchSvc<T> {
+                        type Response = super::SearchResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SearchRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Metadata>::search(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SearchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/Trending" => {
+                    #[allow(non_camel_case_types)]
+                    struct TrendingSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::TrendingRequest> for TrendingSvc<T> {
+                        type Response = super::TrendingResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TrendingRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Metadata>::trending(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = TrendingSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/Related" => {
+                    #[allow(non_camel_case_types)]
+                    struct RelatedSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::RelatedRequest> for RelatedSvc<T> {
+                        type Response = super::RelatedResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RelatedRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut =
+                                async move { <T as Metadata>::related(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RelatedSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/Import" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::ImportRequest> for ImportSvc<T> {
+                        type Response = super::ImportReport;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ImportRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Metadata>::import(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ImportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/metadata.Metadata/Export" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExportSvc<T: Metadata>(pub Arc<T>);
+                    impl<T: Metadata> tonic::server::UnaryService<super::ExportRequest> for ExportSvc<T> {
+                        type Response = super::ExportResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExportRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { <T as Metadata>::export(&inner, request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ExportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
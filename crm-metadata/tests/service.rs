@@ -6,17 +6,18 @@ use crm_metadata::{
     AppConfig, MetadataService,
 };
 use futures::StreamExt;
+use sqlx_db_tester::TestPg;
 use tokio::time::sleep;
 use tonic::{transport::Server, Request};
 
 #[tokio::test]
 async fn test_metadata() -> Result<()> {
-    let addr = start_server().await?;
+    let (_tdb, addr) = start_server().await?;
     let mut client = MetadataClient::connect(format!("http://{}", addr)).await?;
     let var_name = vec![
-        MaterializeRequest { id: 1 },
-        MaterializeRequest { id: 2 },
-        MaterializeRequest { id: 3 },
+        MaterializeRequest::new(1, ""),
+        MaterializeRequest::new(2, ""),
+        MaterializeRequest::new(3, ""),
     ];
     let stream = tokio_stream::iter(var_name);
     let request = Request::new(stream);
@@ -24,23 +25,25 @@ async fn test_metadata() -> Result<()> {
     let ret: Vec<_> = response.then(|res| async { res.unwrap() }).collect().await;
 
     assert_eq!(ret.len(), 3);
+    assert_eq!(ret[0].url, "https://acme.org/contents/1");
 
     Ok(())
 }
 
-async fn start_server() -> Result<SocketAddr> {
+async fn start_server() -> Result<(TestPg, SocketAddr)> {
     let config = AppConfig::load()?;
     let addr = format!("[::1]:{}", config.server.port).parse()?;
 
-    let svc = MetadataService::new(config).into_server();
+    let (tdb, svc) = MetadataService::new_for_test().await?;
+
     tokio::spawn(async move {
         Server::builder()
-            .add_service(svc)
+            .add_service(svc.into_server())
             .serve(addr)
             .await
             .unwrap();
     });
     sleep(Duration::from_micros(1)).await;
 
-    Ok(addr)
+    Ok((tdb, addr))
 }
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use proto_builder_trait::tonic::BuilderAttributes;
 use std::fs;
 
 fn main() -> Result<()> {
@@ -8,6 +9,11 @@ fn main() -> Result<()> {
 
     builder
         .out_dir(path)
+        .with_serde(&["SmsMessage", "InAppMessage"], true, true, None)
+        .with_type_attributes(
+            &["SendRequest.msg"],
+            &["#[allow(clippy::large_enum_variant)]"],
+        )
         .compile(
             &[
                 "../protos/notification/messages.proto",
@@ -0,0 +1,64 @@
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use crm_send::{
+    verify_signature, WebhookEvent, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+use tracing::{info, level_filters::LevelFilter, warn};
+use tracing_subscriber::{fmt::Layer, layer::SubscriberExt, util::SubscriberInitExt, Layer as _};
+
+/// receive the webhook events of crm-send locally, and print the ones with a valid signature.
+/// Usage: WEBHOOK_SECRET=change-me cargo run --example webhook_receiver [port]
+#[tokio::main]
+async fn main() -> Result<()> {
+    let layer = Layer::new().with_filter(LevelFilter::INFO);
+    tracing_subscriber::registry().with(layer).init();
+
+    let secret = std::env::var("WEBHOOK_SECRET").unwrap_or_else(|_| "change-me".to_string());
+    let port: u16 = match std::env::args().nth(1) {
+        Some(port) => port.parse()?,
+        None => 9100,
+    };
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    info!("Webhook receiver listening on http://{}/events", addr);
+
+    let app = Router::new()
+        .route("/events", post(receive))
+        .with_state(secret);
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await?;
+    Ok(())
+}
+
+async fn receive(State(secret): State<String>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    let timestamp = header(WEBHOOK_TIMESTAMP_HEADER).and_then(|v| v.parse().ok());
+    let signature = header(WEBHOOK_SIGNATURE_HEADER).unwrap_or_default();
+    let Some(timestamp) = timestamp else {
+        warn!("Missing {} header", WEBHOOK_TIMESTAMP_HEADER);
+        return StatusCode::BAD_REQUEST;
+    };
+    if !verify_signature(&secret, timestamp, &body, signature) {
+        warn!("Invalid signature: {}", signature);
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    match serde_json::from_slice::<WebhookEvent>(&body) {
+        Ok(event) => {
+            info!("{:?}", event);
+            StatusCode::OK
+        }
+        Err(e) => {
+            warn!("Invalid event: {:?}", e);
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
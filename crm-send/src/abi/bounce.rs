@@ -0,0 +1,301 @@
+use anyhow::{anyhow, Result};
+use mail_parser::{Message, MessageParser, MimeHeaders};
+use tonic::{Response, Status};
+use tracing::{info, warn};
+
+use crate::{
+    pb::{
+        report_bounce_request::Report, Bounce, BounceKind, DeliveryStatus, ReportBounceRequest,
+        ReportBounceResponse,
+    },
+    NotificationService, ServiceResult,
+};
+
+use super::{email::MESSAGE_ID_DOMAIN, recipient::normalize_recipient};
+
+impl NotificationService {
+    /// update the status of the bounced messages, and suppress the recipients of hard bounces
+    /// and complaints so they are not sent to again
+    pub async fn report_bounce(
+        &self,
+        req: ReportBounceRequest,
+    ) -> ServiceResult<ReportBounceResponse> {
+        let bounces = match req.report {
+            Some(Report::Bounce(bounce)) => vec![bounce],
+            Some(Report::Dsn(dsn)) => match parse_dsn(&dsn) {
+                Ok(bounces) => bounces,
+                Err(e) => {
+                    warn!("Failed to parse DSN: {:?}", e);
+                    return Err(Status::invalid_argument(e.to_string()));
+                }
+            },
+            None => return Err(Status::invalid_argument("Missing bounce report")),
+        };
+
+        let mut suppressed = vec![];
+        for bounce in bounces.iter() {
+            let kind = bounce.kind();
+            info!(
+                "{:?} bounce of message {:?} from {}: {}",
+                kind, bounce.message_id, bounce.recipient, bounce.detail
+            );
+            metrics::counter!("crm_send_bounces_total", "kind" => kind.as_str_name()).increment(1);
+
+            if !bounce.message_id.is_empty() {
+                let status = match kind {
+                    BounceKind::Complaint => DeliveryStatus::Complained,
+                    _ => DeliveryStatus::Bounced,
+                };
+                let detail = format!("{}: {}", bounce.recipient, bounce.detail);
+                self.status.record(&bounce.message_id, status, detail).await;
+            }
+
+            if matches!(kind, BounceKind::Hard | BounceKind::Complaint) {
+                let recipient = match normalize_recipient(&bounce.recipient) {
+                    Ok(recipient) => recipient,
+                    Err(e) => {
+                        warn!("Can't suppress {:?}: {:?}", bounce.recipient, e);
+                        continue;
+                    }
+                };
+                let reason = match kind {
+                    BounceKind::Complaint => "complaint".to_string(),
+                    _ => format!("hard bounce {}", bounce.detail).trim().to_string(),
+                };
+                if self.suppression.add(&recipient, &reason) {
+                    suppressed.push(recipient);
+                }
+            }
+        }
+
+        Ok(Response::new(ReportBounceResponse {
+            bounces,
+            suppressed,
+        }))
+    }
+}
+
+/// extract the failed recipients of a delivery status notification (RFC 3464), along with
+/// the message_id of the original message if it's attached
+fn parse_dsn(raw: &[u8]) -> Result<Vec<Bounce>> {
+    let msg = MessageParser::default()
+        .parse(raw)
+        .ok_or_else(|| anyhow!("Invalid DSN email"))?;
+
+    let mut status = None;
+    let mut message_id = String::new();
+    for part in msg.parts.iter() {
+        let Some(content_type) = part.content_type() else {
+            continue;
+        };
+        let subtype = content_type.subtype().unwrap_or_default();
+        match (content_type.ctype(), subtype) {
+            ("message", "delivery-status" | "global-delivery-status") => {
+                status = Some(String::from_utf8_lossy(part.contents()).to_string());
+            }
+            ("message", "rfc822" | "global") | ("text", "rfc822-headers") => {
+                if let Some(original) = MessageParser::default().parse(part.contents()) {
+                    message_id = original_message_id(&original);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let status = status.ok_or_else(|| anyhow!("No delivery-status part in DSN"))?;
+    let bounces: Vec<_> = dsn_fields(&status)
+        .into_iter()
+        // the first group has the per-message fields
+        .skip(1)
+        .filter_map(|fields| to_bounce(&fields, &message_id))
+        .collect();
+    if bounces.is_empty() {
+        return Err(anyhow!("No failed recipients in DSN"));
+    }
+    Ok(bounces)
+}
+
+fn original_message_id(msg: &Message) -> String {
+    let id = msg.message_id().unwrap_or_default();
+    match id.rsplit_once('@') {
+        Some((id, MESSAGE_ID_DOMAIN)) => id.to_string(),
+        // not sent by us, keep it as is for the status
+        _ => id.to_string(),
+    }
+}
+
+/// the delivery-status body is groups of header fields separated by blank lines
+fn dsn_fields(status: &str) -> Vec<Vec<(String, String)>> {
+    let mut groups = vec![];
+    let mut fields: Vec<(String, String)> = vec![];
+    for line in status.lines() {
+        if line.trim().is_empty() {
+            if !fields.is_empty() {
+                groups.push(std::mem::take(&mut fields));
+            }
+        } else if line.starts_with([' ', '\t']) {
+            // folded line
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            fields.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    if !fields.is_empty() {
+        groups.push(fields);
+    }
+    groups
+}
+
+fn to_bounce(fields: &[(String, String)], message_id: &str) -> Option<Bounce> {
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    // e.g. "rfc822; tyr@acme.org", the address type is optional
+    let recipient = field("final-recipient").or_else(|| field("original-recipient"))?;
+    let recipient = recipient.rsplit(';').next().unwrap_or_default().trim();
+    let action = field("action").unwrap_or_default().to_lowercase();
+    let status = field("status").unwrap_or_default();
+
+    let kind = match action.as_str() {
+        "failed" if status.starts_with('4') => BounceKind::Soft,
+        "failed" => BounceKind::Hard,
+        "delayed" => BounceKind::Soft,
+        // delivered, relayed or expanded
+        _ => return None,
+    };
+    let detail = match field("diagnostic-code") {
+        Some(diagnostic) => format!("{} {}", status, diagnostic),
+        None => status.to_string(),
+    };
+    Some(Bounce {
+        message_id: message_id.to_string(),
+        recipient: recipient.to_string(),
+        kind: kind as i32,
+        detail: detail.trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pb::{send_request::Msg, EmailMessage, GetStatusRequest},
+        AppConfig,
+    };
+
+    const DSN: &str = "From: MAILER-DAEMON@mx.acme.org\r
+To: bounces@crm.example.com\r
+Subject: Undelivered Mail Returned to Sender\r
+MIME-Version: 1.0\r
+Content-Type: multipart/report; report-type=delivery-status; boundary=\"BOUNDARY\"\r
+\r
+--BOUNDARY\r
+Content-Type: text/plain\r
+\r
+Your message could not be delivered.\r
+--BOUNDARY\r
+Content-Type: message/delivery-status\r
+\r
+Reporting-MTA: dns; mx.acme.org\r
+Arrival-Date: Mon, 6 Jan 2025 10:00:00 +0000\r
+\r
+Final-Recipient: rfc822; Gone@Acme.org\r
+Action: failed\r
+Status: 5.1.1\r
+Diagnostic-Code: smtp; 550 5.1.1 <gone@acme.org>:\r
+  Recipient address rejected: User unknown\r
+\r
+Final-Recipient: rfc822; full@acme.org\r
+Action: delayed\r
+Status: 4.2.2\r
+\r
+Final-Recipient: rfc822; ok@acme.org\r
+Action: delivered\r
+Status: 2.0.0\r
+--BOUNDARY\r
+Content-Type: text/rfc822-headers\r
+\r
+Message-ID: <MESSAGE_ID@crm-send>\r
+From: crm@example.com\r
+Subject: Hello\r
+--BOUNDARY--\r
+";
+
+    #[test]
+    fn dsn_should_be_parsed() -> Result<()> {
+        let bounces = parse_dsn(DSN.replace("MESSAGE_ID", "42").as_bytes())?;
+        assert_eq!(bounces.len(), 2);
+
+        assert_eq!(bounces[0].message_id, "42");
+        assert_eq!(bounces[0].recipient, "Gone@Acme.org");
+        assert_eq!(bounces[0].kind(), BounceKind::Hard);
+        assert_eq!(
+            bounces[0].detail,
+            "5.1.1 smtp; 550 5.1.1 <gone@acme.org>: Recipient address rejected: User unknown"
+        );
+        assert_eq!(bounces[1].recipient, "full@acme.org");
+        assert_eq!(bounces[1].kind(), BounceKind::Soft);
+
+        assert!(parse_dsn(b"Subject: Hello\r\n\r\nnot a DSN").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn hard_bounce_should_suppress_recipient() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let email = EmailMessage::fake();
+        let message_id = email.message_id.clone();
+
+        let req = ReportBounceRequest {
+            report: Some(Report::Dsn(DSN.replace("MESSAGE_ID", &message_id).into())),
+        };
+        let res = service.report_bounce(req).await?.into_inner();
+        assert_eq!(res.suppressed, ["Gone@acme.org"]);
+
+        let status = service
+            .get_status(GetStatusRequest { message_id })
+            .await?
+            .into_inner();
+        assert_eq!(status.status, DeliveryStatus::Bounced as i32);
+
+        let mut email = EmailMessage::fake();
+        email.recipients = vec!["Gone@ACME.org".to_string()];
+        let res = service
+            .dispatch(Msg::Email(email), Default::default())
+            .await?;
+        assert_eq!(res.status, DeliveryStatus::Failed as i32);
+        assert_eq!(
+            res.invalid_recipients[0].reason,
+            "suppressed: hard bounce 5.1.1 smtp; 550 5.1.1 <gone@acme.org>: Recipient address rejected: User unknown"
+        );
+
+        // soft bounces and unknown kinds don't suppress
+        let req = ReportBounceRequest {
+            report: Some(Report::Bounce(Bounce {
+                recipient: "+14155552671".to_string(),
+                kind: BounceKind::Soft as i32,
+                ..Default::default()
+            })),
+        };
+        let res = service.report_bounce(req).await?.into_inner();
+        assert!(res.suppressed.is_empty());
+
+        let req = ReportBounceRequest {
+            report: Some(Report::Bounce(Bounce {
+                recipient: "+1 415 555 2671".to_string(),
+                kind: BounceKind::Complaint as i32,
+                ..Default::default()
+            })),
+        };
+        let res = service.report_bounce(req).await?.into_inner();
+        assert_eq!(res.suppressed, ["+14155552671"]);
+        Ok(())
+    }
+}
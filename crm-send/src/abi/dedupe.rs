@@ -0,0 +1,386 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use prost_types::Timestamp;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use tracing::warn;
+
+use crate::{
+    config::DedupeConfig,
+    pb::{DeliveryStatus, RecipientError, SendResponse},
+};
+
+use super::to_ts;
+
+/// how often the expired entries are dropped, in seconds
+const SWEEP_INTERVAL: i64 = 60;
+/// the journal is rewritten with the live entries once it has this many lines, and twice
+/// as many as it had after the last rewrite
+const COMPACT_MIN_LINES: usize = 10_000;
+
+/// remembers the response of every message_id within the ttl, so a retried send
+/// gets the original response instead of being delivered again
+pub struct Dedupe {
+    ttl: i64,
+    path: Option<PathBuf>,
+    inner: Mutex<DedupeInner>,
+}
+
+struct DedupeInner {
+    entries: HashMap<String, DedupeEntry>,
+    /// reserved, but neither committed nor released yet, so not in the journal
+    pending: HashSet<String>,
+    journal: Option<File>,
+    journal_lines: usize,
+    /// the lines left by the last rewrite of the journal
+    compacted_lines: usize,
+    next_sweep: i64,
+}
+
+/// outcome of reserving a message_id
+#[derive(Debug, PartialEq)]
+pub enum Reservation {
+    /// not seen within the ttl, go on sending it
+    New,
+    /// already sent, with its original response
+    Sent(SendResponse),
+    /// reserved by another send which is not done yet, so whether it's sent is unknown
+    InFlight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DedupeEntry {
+    message_id: String,
+    seconds: i64,
+    nanos: i32,
+    expires_at: i64,
+    /// (recipient, reason) of the recipients dropped by validation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    invalid_recipients: Vec<(String, String)>,
+}
+
+impl Dedupe {
+    /// load the live entries from the journal and compact it
+    pub fn load(config: &DedupeConfig) -> Result<Self> {
+        let ttl = config.ttl as i64;
+        let mut entries = HashMap::new();
+        let mut journal = None;
+
+        if let Some(path) = &config.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .context(format!("Failed to create dedupe dir: {:?}", parent))?;
+            }
+
+            if let Ok(file) = File::open(path) {
+                let now = Utc::now().timestamp();
+                for line in BufReader::new(file).lines() {
+                    let line = line.context("Failed to read dedupe journal")?;
+                    match serde_json::from_str::<DedupeEntry>(&line) {
+                        Ok(entry) if entry.expires_at > now => {
+                            entries.insert(entry.message_id.clone(), entry);
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Skip invalid dedupe entry {:?}: {:?}", line, e),
+                    }
+                }
+            }
+
+            journal = Some(write_journal(path, entries.values())?);
+        }
+
+        let inner = DedupeInner {
+            journal_lines: entries.len(),
+            compacted_lines: entries.len(),
+            entries,
+            pending: HashSet::new(),
+            journal,
+            next_sweep: Utc::now().timestamp() + SWEEP_INTERVAL,
+        };
+        Ok(Self {
+            ttl,
+            path: config.path.clone(),
+            inner: Mutex::new(inner),
+        })
+    }
+
+    /// reserve the message_id for sending, unless it's a duplicate of a sent or in-flight one
+    pub fn reserve(&self, message_id: &str) -> Reservation {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Utc::now().timestamp();
+        if now >= inner.next_sweep {
+            inner.entries.retain(|_, entry| entry.expires_at > now);
+            inner.next_sweep = now + SWEEP_INTERVAL;
+        }
+        match inner.entries.get(message_id) {
+            Some(entry) if entry.expires_at <= now => {}
+            Some(_) if inner.pending.contains(message_id) => return Reservation::InFlight,
+            Some(entry) => return Reservation::Sent(entry.into()),
+            None => {}
+        }
+
+        let ts = to_ts();
+        let entry = DedupeEntry {
+            message_id: message_id.to_string(),
+            seconds: ts.seconds,
+            nanos: ts.nanos,
+            expires_at: now + self.ttl,
+            invalid_recipients: vec![],
+        };
+        inner.pending.insert(entry.message_id.clone());
+        inner.entries.insert(entry.message_id.clone(), entry);
+        Reservation::New
+    }
+
+    /// remember the response of a sent message, and persist it to the journal. The reserved
+    /// timestamp is kept, so duplicates sent in the meantime get the same response
+    pub fn commit(&self, res: SendResponse) -> SendResponse {
+        let mut inner = self.inner.lock().unwrap();
+        let ts = match inner.entries.get(&res.message_id) {
+            Some(entry) => Timestamp {
+                seconds: entry.seconds,
+                nanos: entry.nanos,
+            },
+            None => res.timestamp.unwrap_or_else(to_ts),
+        };
+        let entry = DedupeEntry {
+            message_id: res.message_id,
+            seconds: ts.seconds,
+            nanos: ts.nanos,
+            expires_at: Utc::now().timestamp() + self.ttl,
+            invalid_recipients: res
+                .invalid_recipients
+                .into_iter()
+                .map(|e| (e.recipient, e.reason))
+                .collect(),
+        };
+
+        if let Some(journal) = inner.journal.as_mut() {
+            let ret = serde_json::to_string(&entry)
+                .map_err(anyhow::Error::from)
+                .and_then(|line| Ok(writeln!(journal, "{}", line)?));
+            match ret {
+                Ok(()) => inner.journal_lines += 1,
+                Err(e) => warn!("Failed to write dedupe journal {:?}: {:?}", self.path, e),
+            }
+        }
+        let res = SendResponse::from(&entry);
+        inner.pending.remove(&entry.message_id);
+        inner.entries.insert(entry.message_id.clone(), entry);
+        self.compact(&mut inner);
+        res
+    }
+
+    /// forget a reserved message_id whose send failed, so it can be retried
+    pub fn release(&self, message_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pending.remove(message_id);
+        inner.entries.remove(message_id);
+    }
+
+    /// rewrite the journal with the live committed entries once it's mostly stale lines
+    fn compact(&self, inner: &mut DedupeInner) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if inner.journal_lines < COMPACT_MIN_LINES.max(inner.compacted_lines * 2) {
+            return;
+        }
+
+        let now = Utc::now().timestamp();
+        inner.entries.retain(|_, entry| entry.expires_at > now);
+        let pending = &inner.pending;
+        let live: Vec<_> = inner
+            .entries
+            .values()
+            .filter(|entry| !pending.contains(&entry.message_id))
+            .collect();
+        let lines = live.len();
+        match write_journal(path, live.into_iter()) {
+            Ok(journal) => {
+                inner.journal = Some(journal);
+                inner.journal_lines = lines;
+                inner.compacted_lines = lines;
+            }
+            Err(e) => warn!("Failed to compact dedupe journal {:?}: {:?}", path, e),
+        }
+    }
+}
+
+impl From<&DedupeEntry> for SendResponse {
+    fn from(entry: &DedupeEntry) -> Self {
+        SendResponse {
+            message_id: entry.message_id.clone(),
+            timestamp: Some(Timestamp {
+                seconds: entry.seconds,
+                nanos: entry.nanos,
+            }),
+            status: DeliveryStatus::Accepted as i32,
+            invalid_recipients: entry
+                .invalid_recipients
+                .iter()
+                .map(|(recipient, reason)| RecipientError {
+                    recipient: recipient.clone(),
+                    reason: reason.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// write the entries to a temp file next to the journal and swap it in, so a crash never
+/// leaves a partial journal behind
+fn write_journal<'a>(
+    path: &PathBuf,
+    entries: impl Iterator<Item = &'a DedupeEntry>,
+) -> Result<File> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut tmp = NamedTempFile::new_in(dir).context(format!("Failed to create in {:?}", dir))?;
+    for entry in entries {
+        writeln!(tmp, "{}", serde_json::to_string(entry)?)?;
+    }
+    tmp.as_file().sync_all()?;
+    tmp.persist(path)
+        .context(format!("Failed to replace {:?}", path))?;
+
+    let file = OpenOptions::new()
+        .append(true)
+        .open(path)
+        .context(format!("Failed to open {:?}", path))?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(message_id: &str) -> SendResponse {
+        SendResponse {
+            message_id: message_id.to_string(),
+            timestamp: Some(to_ts()),
+            status: DeliveryStatus::Accepted as i32,
+            invalid_recipients: vec![],
+        }
+    }
+
+    #[test]
+    fn dedupe_should_survive_restart() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("dedupe.jsonl");
+        let config = DedupeConfig {
+            ttl: 60,
+            path: Some(path.clone()),
+        };
+
+        let dedupe = Dedupe::load(&config)?;
+        assert_eq!(dedupe.reserve("1"), Reservation::New);
+        let res = dedupe.commit(SendResponse {
+            message_id: "1".to_string(),
+            timestamp: Some(to_ts()),
+            status: DeliveryStatus::Accepted as i32,
+            invalid_recipients: vec![RecipientError {
+                recipient: "foo".to_string(),
+                reason: "missing @".to_string(),
+            }],
+        });
+        assert_eq!(dedupe.reserve("2"), Reservation::New);
+        dedupe.release("2");
+        drop(dedupe);
+
+        let dedupe = Dedupe::load(&config)?;
+        assert_eq!(dedupe.reserve("1"), Reservation::Sent(res));
+        assert_eq!(dedupe.reserve("2"), Reservation::New);
+        Ok(())
+    }
+
+    #[test]
+    fn dedupe_should_forget_expired_entries() -> Result<()> {
+        let config = DedupeConfig { ttl: 0, path: None };
+        let dedupe = Dedupe::load(&config)?;
+        assert_eq!(dedupe.reserve("1"), Reservation::New);
+        assert_eq!(dedupe.reserve("1"), Reservation::New);
+
+        // the expired entries are dropped from memory on the next sweep
+        dedupe.inner.lock().unwrap().next_sweep = 0;
+        assert_eq!(dedupe.reserve("2"), Reservation::New);
+        let inner = dedupe.inner.lock().unwrap();
+        assert_eq!(inner.entries.keys().collect::<Vec<_>>(), ["2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn dedupe_should_compact_journal() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("dedupe.jsonl");
+        let config = DedupeConfig {
+            ttl: 60,
+            path: Some(path.clone()),
+        };
+        let dedupe = Dedupe::load(&config)?;
+        // every commit of the same message_id is another line
+        for _ in 0..COMPACT_MIN_LINES - 1 {
+            dedupe.commit(response("1"));
+        }
+        let lines = || fs::read_to_string(&path).map(|s| s.lines().count());
+        assert_eq!(lines()?, COMPACT_MIN_LINES - 1);
+
+        // a pending reservation is not persisted by the compaction
+        assert_eq!(dedupe.reserve("pending"), Reservation::New);
+        dedupe.commit(response("2"));
+        assert_eq!(lines()?, 2);
+        dedupe.commit(response("3"));
+        assert_eq!(lines()?, 3);
+
+        let dedupe = Dedupe::load(&config)?;
+        assert!(matches!(dedupe.reserve("1"), Reservation::Sent(_)));
+        assert_eq!(dedupe.reserve("pending"), Reservation::New);
+        Ok(())
+    }
+
+    #[test]
+    fn dedupe_should_hold_back_duplicates_in_flight() -> Result<()> {
+        let dedupe = Dedupe::load(&DedupeConfig::default())?;
+        assert_eq!(dedupe.reserve("1"), Reservation::New);
+        assert_eq!(dedupe.reserve("1"), Reservation::InFlight);
+        let res = dedupe.commit(response("1"));
+        assert_eq!(dedupe.reserve("1"), Reservation::Sent(res));
+
+        assert_eq!(dedupe.reserve("2"), Reservation::New);
+        dedupe.release("2");
+        assert_eq!(dedupe.reserve("2"), Reservation::New);
+        Ok(())
+    }
+
+    #[test]
+    fn dedupe_journals_should_not_share_temp_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("dedupe.jsonl");
+        fs::write(dir.path().join("dedupe.tmp"), "not a journal")?;
+        let config = DedupeConfig {
+            ttl: 60,
+            path: Some(path.clone()),
+        };
+
+        let dedupe = Dedupe::load(&config)?;
+        dedupe.reserve("1");
+        dedupe.commit(response("1"));
+        let other = Dedupe::load(&config)?;
+        assert!(matches!(other.reserve("1"), Reservation::Sent(_)));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("dedupe.tmp"))?,
+            "not a journal"
+        );
+        Ok(())
+    }
+}
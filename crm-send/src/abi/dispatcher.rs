@@ -0,0 +1,506 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex as StdMutex},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::{
+    sync::{mpsc, Mutex, Notify},
+    time::{sleep, timeout_at, Instant},
+};
+use tracing::{info, warn};
+
+use crate::{
+    config::{DispatchConfig, LaneWeights},
+    pb::{send_request::Msg, DeliveryStatus, Priority},
+};
+
+use super::{RateLimiter, Sink, StatusStore, Tracker, CHANNEL_SIZE};
+
+const CHANNELS: [&str; 3] = ["email", "sms", "in_app"];
+
+/// routes messages to a queue per channel and priority. Each channel is drained by its own
+/// pool of workers, so a slow channel doesn't hold back the others, and the workers share
+/// themselves between the priority lanes by weight, so a campaign backlog in the low lane
+/// doesn't hold back transactional messages in the high lane
+pub struct Dispatcher {
+    queues: HashMap<&'static str, [mpsc::Sender<Job>; 3]>,
+    status: StatusStore,
+    in_flight: Arc<InFlight>,
+}
+
+/// the messages accepted but not sent yet, so a shutdown could wait for them
+#[derive(Default)]
+struct InFlight {
+    jobs: StdMutex<HashMap<String, JobState>>,
+    done: Notify,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobState {
+    /// held until its send_at
+    Scheduled,
+    /// in a queue, or being sent
+    Queued,
+}
+
+/// the messages left undelivered when a drain ends
+#[derive(Debug, Default)]
+pub struct DrainReport {
+    /// queued, but not sent before the deadline
+    pub queued: Vec<String>,
+    /// scheduled for later. They're held in memory only, so they're lost on shutdown and
+    /// must be sent again by the caller
+    pub scheduled: Vec<String>,
+}
+
+/// a message queued for delivery, along with its delivery window
+#[derive(Debug)]
+pub struct Job {
+    pub msg: Msg,
+    pub schedule: Schedule,
+    queued_at: Instant,
+}
+
+/// when a message should be sent, when it's no longer worth sending, and in which lane
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Schedule {
+    pub send_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
+}
+
+/// the priority lanes of a channel, drained by smooth weighted round robin
+struct Lanes {
+    rxs: [mpsc::Receiver<Job>; 3],
+    weights: [i64; 3],
+    current: [i64; 3],
+    pending: [Option<Job>; 3],
+}
+
+impl Dispatcher {
+    pub fn new(
+        config: &DispatchConfig,
+        status: StatusStore,
+        limiter: RateLimiter,
+        sink: Sink,
+        tracker: Option<Arc<Tracker>>,
+    ) -> Self {
+        let limiter = Arc::new(limiter);
+        let sink = Arc::new(sink);
+        let in_flight = Arc::new(InFlight::default());
+        let queues = CHANNELS
+            .into_iter()
+            .map(|channel| {
+                let workers = config.workers.get(channel).copied().unwrap_or(1).max(1);
+                let (high, high_rx) = mpsc::channel(CHANNEL_SIZE);
+                let (normal, normal_rx) = mpsc::channel(CHANNEL_SIZE);
+                let (low, low_rx) = mpsc::channel(CHANNEL_SIZE);
+                let lanes = Lanes::new([high_rx, normal_rx, low_rx], &config.weights);
+                let lanes = Arc::new(Mutex::new(lanes));
+                // weak, so the workers stop once the dispatcher is dropped
+                let requeue = [&high, &normal, &low].map(|queue| queue.downgrade());
+                for _ in 0..workers {
+                    tokio::spawn(worker(
+                        lanes.clone(),
+                        requeue.clone(),
+                        status.clone(),
+                        limiter.clone(),
+                        sink.clone(),
+                        tracker.clone(),
+                        in_flight.clone(),
+                    ));
+                }
+                (channel, [high, normal, low])
+            })
+            .collect();
+        Self {
+            queues,
+            status,
+            in_flight,
+        }
+    }
+
+    /// queue the job, or hold it in memory until its send_at if it's scheduled for later
+    pub async fn send(&self, job: Job) -> Result<(), mpsc::error::SendError<Job>> {
+        let Some(queues) = self.queues.get(job.msg.channel()) else {
+            return Err(mpsc::error::SendError(job));
+        };
+        let queue = queues[lane_of(job.schedule.priority)].clone();
+
+        let delay = job
+            .schedule
+            .send_at
+            .and_then(|send_at| (send_at - Utc::now()).to_std().ok());
+        let id = job.msg.message_id().to_string();
+        match delay {
+            Some(delay) if !delay.is_zero() => {
+                let status = self.status.clone();
+                let in_flight = self.in_flight.clone();
+                in_flight.insert(&id, JobState::Scheduled);
+                tokio::spawn(async move {
+                    sleep(delay).await;
+                    in_flight.insert(&id, JobState::Queued);
+                    if let Err(e) = queue.send(job.requeued()).await {
+                        warn!("Failed to queue scheduled message: {:?}", e.0.msg);
+                        status
+                            .record(e.0.msg.message_id(), DeliveryStatus::Failed, "queue closed")
+                            .await;
+                        in_flight.remove(&id);
+                    }
+                });
+                Ok(())
+            }
+            _ => {
+                self.in_flight.insert(&id, JobState::Queued);
+                let ret = queue.send(job).await;
+                if ret.is_err() {
+                    self.in_flight.remove(&id);
+                }
+                ret
+            }
+        }
+    }
+
+    /// wait until the queues are empty or the deadline is reached, and report the messages
+    /// left undelivered. Messages scheduled for later than the deadline are not waited for
+    pub async fn drain(&self, deadline: Instant) -> DrainReport {
+        loop {
+            // register before checking, so a job done in between is not missed
+            let done = self.in_flight.done.notified();
+            if !self.in_flight.has(JobState::Queued) {
+                break;
+            }
+            if timeout_at(deadline, done).await.is_err() {
+                break;
+            }
+        }
+
+        let mut report = DrainReport::default();
+        for (id, state) in self.in_flight.jobs.lock().unwrap().iter() {
+            match state {
+                JobState::Queued => report.queued.push(id.clone()),
+                JobState::Scheduled => report.scheduled.push(id.clone()),
+            }
+        }
+        report.queued.sort();
+        report.scheduled.sort();
+        report
+    }
+}
+
+impl Job {
+    pub fn new(msg: impl Into<Msg>, schedule: Schedule) -> Self {
+        Self {
+            msg: msg.into(),
+            schedule,
+            queued_at: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.schedule
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+
+    fn requeued(self) -> Self {
+        Self {
+            queued_at: Instant::now(),
+            ..self
+        }
+    }
+}
+
+impl InFlight {
+    fn insert(&self, id: &str, state: JobState) {
+        self.jobs.lock().unwrap().insert(id.to_string(), state);
+    }
+
+    fn remove(&self, id: &str) {
+        self.jobs.lock().unwrap().remove(id);
+        self.done.notify_waiters();
+    }
+
+    fn has(&self, state: JobState) -> bool {
+        self.jobs.lock().unwrap().values().any(|s| *s == state)
+    }
+}
+
+impl DrainReport {
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty() && self.scheduled.is_empty()
+    }
+}
+
+impl Lanes {
+    fn new(rxs: [mpsc::Receiver<Job>; 3], weights: &LaneWeights) -> Self {
+        let weights = [weights.high, weights.normal, weights.low].map(|w| w.max(1) as i64);
+        Self {
+            rxs,
+            weights,
+            current: [0; 3],
+            pending: [None, None, None],
+        }
+    }
+
+    /// get the next job: among the lanes with jobs ready, each lane is picked
+    /// in proportion to its weight, without long runs of the same lane
+    async fn recv(&mut self) -> Option<Job> {
+        loop {
+            for (rx, pending) in self.rxs.iter_mut().zip(self.pending.iter_mut()) {
+                if pending.is_none() {
+                    *pending = rx.try_recv().ok();
+                }
+            }
+
+            let ready: Vec<_> = (0..3).filter(|i| self.pending[*i].is_some()).collect();
+            if !ready.is_empty() {
+                let total: i64 = ready.iter().map(|i| self.weights[*i]).sum();
+                for i in &ready {
+                    self.current[*i] += self.weights[*i];
+                }
+                let picked = *ready.iter().max_by_key(|i| self.current[**i]).unwrap();
+                self.current[picked] -= total;
+                return self.pending[picked].take();
+            }
+
+            // nothing ready, wait for any lane
+            let [high, normal, low] = &mut self.rxs;
+            let (i, job) = tokio::select! {
+                biased;
+                Some(job) = high.recv() => (0, job),
+                Some(job) = normal.recv() => (1, job),
+                Some(job) = low.recv() => (2, job),
+                else => return None,
+            };
+            self.pending[i] = Some(job);
+        }
+    }
+}
+
+async fn worker(
+    lanes: Arc<Mutex<Lanes>>,
+    requeue: [mpsc::WeakSender<Job>; 3],
+    status: StatusStore,
+    limiter: Arc<RateLimiter>,
+    sink: Arc<Sink>,
+    tracker: Option<Arc<Tracker>>,
+    in_flight: Arc<InFlight>,
+) {
+    loop {
+        // only hold the lock while waiting, so the other workers could send in parallel
+        let job = lanes.lock().await.recv().await;
+        let Some(job) = job else {
+            break;
+        };
+        let id = job.msg.message_id().to_string();
+        let expired = job.is_expired();
+        if !expired {
+            if let Some(wait) = limiter.try_acquire(&job.msg) {
+                // put it back in its lane once the wait is over, and move on to the next job
+                let queue = &requeue[lane_of(job.schedule.priority)];
+                requeue_after(wait, job, queue, &status, &in_flight);
+                continue;
+            }
+        }
+
+        let priority = job.schedule.priority.as_str_name();
+        metrics::histogram!("crm_send_queue_wait_seconds", "priority" => priority)
+            .record(job.queued_at.elapsed().as_secs_f64());
+        if expired {
+            info!("Message {} expired, drop it", id);
+            status
+                .record(
+                    &id,
+                    DeliveryStatus::Expired,
+                    "not delivered before expires_at",
+                )
+                .await;
+            metrics::counter!("crm_send_expired_total", "channel" => job.msg.channel())
+                .increment(1);
+        } else {
+            sink.send(job.msg, &status, tracker.as_deref()).await;
+        }
+        in_flight.remove(&id);
+    }
+}
+
+/// queue the throttled job again after the wait, keeping its queued_at so the wait counts
+/// as time in the queue
+fn requeue_after(
+    wait: std::time::Duration,
+    job: Job,
+    queue: &mpsc::WeakSender<Job>,
+    status: &StatusStore,
+    in_flight: &Arc<InFlight>,
+) {
+    let queue = queue.upgrade();
+    let status = status.clone();
+    let in_flight = in_flight.clone();
+    tokio::spawn(async move {
+        sleep(wait).await;
+        let ret = match queue {
+            Some(queue) => queue.send(job).await.map_err(|e| e.0),
+            None => Err(job),
+        };
+        if let Err(job) = ret {
+            let id = job.msg.message_id();
+            warn!("Failed to queue throttled message: {}", id);
+            status
+                .record(id, DeliveryStatus::Failed, "queue closed")
+                .await;
+            in_flight.remove(id);
+        }
+    });
+}
+
+fn lane_of(priority: Priority) -> usize {
+    match priority {
+        Priority::High => 0,
+        Priority::Normal | Priority::Unspecified => 1,
+        Priority::Low => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{BucketConfig, RateLimitConfig},
+        pb::EmailMessage,
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn lanes_should_share_by_weight() {
+        let weights = LaneWeights {
+            high: 4,
+            normal: 2,
+            low: 1,
+        };
+        let (high, high_rx) = mpsc::channel(CHANNEL_SIZE);
+        let (normal, normal_rx) = mpsc::channel(CHANNEL_SIZE);
+        let (low, low_rx) = mpsc::channel(CHANNEL_SIZE);
+        let mut lanes = Lanes::new([high_rx, normal_rx, low_rx], &weights);
+
+        let queues = [
+            (high, Priority::High),
+            (normal, Priority::Normal),
+            (low, Priority::Low),
+        ];
+        for (queue, priority) in queues.iter() {
+            for _ in 0..70 {
+                let schedule = Schedule {
+                    priority: *priority,
+                    ..Default::default()
+                };
+                queue
+                    .send(Job::new(EmailMessage::fake(), schedule))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let mut picked = HashMap::new();
+        for _ in 0..70 {
+            let job = lanes.recv().await.unwrap();
+            *picked.entry(job.schedule.priority).or_insert(0) += 1;
+        }
+        assert_eq!(picked[&Priority::High], 40);
+        assert_eq!(picked[&Priority::Normal], 20);
+        assert_eq!(picked[&Priority::Low], 10);
+    }
+
+    #[tokio::test]
+    async fn drain_should_report_undelivered_messages() {
+        let status = StatusStore::default();
+        let limiter = RateLimiter::new(&RateLimitConfig::default());
+        let dispatcher =
+            Dispatcher::new(&DispatchConfig::default(), status, limiter, Sink::Log, None);
+
+        let scheduled = EmailMessage::fake();
+        let scheduled_id = scheduled.message_id.clone();
+        let schedule = Schedule {
+            send_at: Some(Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        dispatcher
+            .send(Job::new(scheduled, schedule))
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            let job = Job::new(EmailMessage::fake(), Default::default());
+            dispatcher.send(job).await.unwrap();
+        }
+
+        // the log sink takes a second per message
+        let report = dispatcher
+            .drain(Instant::now() + Duration::from_millis(100))
+            .await;
+        assert_eq!(report.queued.len(), 2);
+        assert_eq!(report.scheduled, [scheduled_id.as_str()]);
+
+        let report = dispatcher
+            .drain(Instant::now() + Duration::from_secs(5))
+            .await;
+        assert!(report.queued.is_empty());
+        assert_eq!(report.scheduled, [scheduled_id]);
+    }
+
+    #[tokio::test]
+    async fn throttled_messages_should_not_hold_workers() {
+        let status = StatusStore::default();
+        let config = RateLimitConfig {
+            channels: HashMap::new(),
+            domains: [(
+                "gmail.com".to_string(),
+                BucketConfig {
+                    rate: 0.1,
+                    burst: 1,
+                },
+            )]
+            .into(),
+        };
+        let limiter = RateLimiter::new(&config);
+        let dispatcher = Dispatcher::new(
+            &DispatchConfig::default(),
+            status.clone(),
+            limiter,
+            Sink::Log,
+            None,
+        );
+
+        let gmail = || {
+            let mut email = EmailMessage::fake();
+            email.recipients = vec!["a@gmail.com".to_string()];
+            email
+        };
+        let (first, throttled, expired, other) = (gmail(), gmail(), gmail(), EmailMessage::fake());
+        let throttled_id = throttled.message_id.clone();
+        let expired_id = expired.message_id.clone();
+        let other_id = other.message_id.clone();
+        let expires = Schedule {
+            expires_at: Some(Utc::now() - chrono::Duration::seconds(1)),
+            ..Default::default()
+        };
+        let jobs = [
+            Job::new(first, Default::default()),
+            Job::new(throttled, Default::default()),
+            Job::new(expired, expires),
+            Job::new(other, Default::default()),
+        ];
+        for job in jobs {
+            dispatcher.send(job).await.unwrap();
+        }
+
+        // the log sink takes a second per message, the throttled one waits for 10
+        let report = dispatcher
+            .drain(Instant::now() + Duration::from_secs(3))
+            .await;
+        assert_eq!(report.queued, [throttled_id]);
+        let status_of = |id: &str| status.get(id).unwrap().status;
+        assert_eq!(status_of(&expired_id), DeliveryStatus::Expired as i32);
+        assert_eq!(status_of(&other_id), DeliveryStatus::Delivered as i32);
+    }
+}
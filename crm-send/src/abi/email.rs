@@ -1,27 +1,180 @@
+use anyhow::{Context, Result};
+use lettre::{
+    message::{
+        header::{ContentType, HeaderName, HeaderValue},
+        Attachment as MimeAttachment, MultiPart, MultiPartBuilder, SinglePart,
+    },
+    Message,
+};
 use tonic::Status;
 use tracing::warn;
 
-use crate::pb::{send_request::Msg, EmailMessage, SendRequest, SendResponse};
+use crate::pb::{
+    send_request::Msg, Attachment, DeliveryStatus, EmailMessage, SendRequest, SendResponse,
+};
 
-use super::{to_ts, Sender};
+use super::{to_ts, Job, Schedule, Sender};
+
+/// domain of the Message-ID header, to tell our messages apart in bounces
+pub(super) const MESSAGE_ID_DOMAIN: &str = "crm-send";
+
+/// attachments are meant for small files, large ones should be linked instead
+const MAX_ATTACHMENTS_SIZE: usize = 10 * 1024 * 1024;
 
 impl Sender for EmailMessage {
     async fn send(
-        self,
+        mut self,
         svc: crate::NotificationService,
+        schedule: Schedule,
     ) -> Result<crate::pb::SendResponse, tonic::Status> {
+        let size: usize = self.attachments.iter().map(|a| a.content.len()).sum();
+        if size > MAX_ATTACHMENTS_SIZE {
+            return Err(Status::invalid_argument(format!(
+                "Attachments too large: {} bytes, max {} bytes",
+                size, MAX_ATTACHMENTS_SIZE
+            )));
+        }
+
+        if let Some(tracker) = svc.tracker.as_ref() {
+            tracker.instrument(&mut self);
+        }
+
         let message_id = self.message_id.clone();
-        svc.sender.send(Msg::Email(self)).await.map_err(|e| {
+        svc.status
+            .record(&message_id, DeliveryStatus::Accepted, "")
+            .await;
+        if let Err(e) = svc.sender.send(Job::new(self, schedule)).await {
             warn!("Failed to send message: {:?}", e);
-            Status::internal("Failed to send message")
-        })?;
+            svc.status
+                .record(&message_id, DeliveryStatus::Failed, "queue closed")
+                .await;
+            return Err(Status::internal("Failed to send message"));
+        }
         Ok(SendResponse {
             message_id,
             timestamp: Some(to_ts()),
+            status: DeliveryStatus::Accepted as i32,
+            ..Default::default()
         })
     }
 }
 
+/// a mime part, either a leaf or a container of other parts
+enum Part {
+    Single(SinglePart),
+    Multi(MultiPart),
+}
+
+impl EmailMessage {
+    /// build the MIME message: plain text, or text and html as multipart/alternative with
+    /// inline images in multipart/related, all wrapped in multipart/mixed with attachments
+    pub fn to_mime(&self) -> Result<Message> {
+        let mut builder = Message::builder()
+            .message_id(Some(format!("<{}@{}>", self.message_id, MESSAGE_ID_DOMAIN)))
+            .from(self.sender.parse().context("Invalid sender")?)
+            .subject(&self.subject);
+        for to in &self.recipients {
+            builder = builder.to(to.parse().context(format!("Invalid recipient: {}", to))?);
+        }
+        for cc in &self.cc {
+            builder = builder.cc(cc.parse().context(format!("Invalid cc: {}", cc))?);
+        }
+        for bcc in &self.bcc {
+            builder = builder.bcc(bcc.parse().context(format!("Invalid bcc: {}", bcc))?);
+        }
+        if !self.reply_to.is_empty() {
+            builder = builder.reply_to(self.reply_to.parse().context("Invalid reply_to")?);
+        }
+        for (name, value) in &self.headers {
+            let name = HeaderName::new_from_ascii(name.clone())
+                .context(format!("Invalid header name: {}", name))?;
+            builder = builder.raw_header(HeaderValue::new(name, value.clone()));
+        }
+
+        // inline images only make sense with an html body to reference them
+        let (inline, attached): (Vec<_>, Vec<_>) = self
+            .attachments
+            .iter()
+            .partition(|a| !a.content_id.is_empty() && !self.html_body.is_empty());
+
+        let text = SinglePart::plain(self.body.clone());
+        let mut body = if self.html_body.is_empty() {
+            Part::Single(text)
+        } else {
+            let html = SinglePart::html(self.html_body.clone());
+            let html = if inline.is_empty() {
+                Part::Single(html)
+            } else {
+                let related = inline
+                    .into_iter()
+                    .try_fold(MultiPart::related().singlepart(html), |related, a| {
+                        Ok::<_, anyhow::Error>(related.singlepart(a.to_inline()?))
+                    })?;
+                Part::Multi(related)
+            };
+            Part::Multi(MultiPart::alternative().singlepart(text).part(html))
+        };
+
+        if !attached.is_empty() {
+            let mixed = MultiPart::mixed().part(body);
+            let mixed = attached.into_iter().try_fold(mixed, |mixed, a| {
+                Ok::<_, anyhow::Error>(mixed.singlepart(a.to_attachment()?))
+            })?;
+            body = Part::Multi(mixed);
+        }
+
+        let msg = match body {
+            Part::Single(part) => builder.singlepart(part),
+            Part::Multi(part) => builder.multipart(part),
+        };
+        msg.context("Failed to build email")
+    }
+}
+
+impl Attachment {
+    fn content_type(&self) -> Result<ContentType> {
+        let content_type = if self.content_type.is_empty() {
+            "application/octet-stream"
+        } else {
+            &self.content_type
+        };
+        ContentType::parse(content_type)
+            .context(format!("Invalid content type: {}", self.content_type))
+    }
+
+    fn to_attachment(&self) -> Result<SinglePart> {
+        Ok(MimeAttachment::new(self.filename.clone())
+            .body(self.content.clone(), self.content_type()?))
+    }
+
+    fn to_inline(&self) -> Result<SinglePart> {
+        Ok(MimeAttachment::new_inline(self.content_id.clone())
+            .body(self.content.clone(), self.content_type()?))
+    }
+}
+
+trait MultiPartExt {
+    fn part(self, part: Part) -> MultiPart;
+}
+
+impl MultiPartExt for MultiPart {
+    fn part(self, part: Part) -> MultiPart {
+        match part {
+            Part::Single(part) => self.singlepart(part),
+            Part::Multi(part) => self.multipart(part),
+        }
+    }
+}
+
+impl MultiPartExt for MultiPartBuilder {
+    fn part(self, part: Part) -> MultiPart {
+        match part {
+            Part::Single(part) => self.singlepart(part),
+            Part::Multi(part) => self.multipart(part),
+        }
+    }
+}
+
 impl From<EmailMessage> for Msg {
     fn from(value: EmailMessage) -> Self {
         Msg::Email(value)
@@ -31,7 +184,10 @@ impl From<EmailMessage> for Msg {
 impl From<EmailMessage> for SendRequest {
     fn from(value: EmailMessage) -> Self {
         let msg: Msg = value.into();
-        SendRequest { msg: Some(msg) }
+        SendRequest {
+            msg: Some(msg),
+            ..Default::default()
+        }
     }
 }
 
@@ -47,6 +203,96 @@ impl EmailMessage {
             recipients: vec![SafeEmail().fake()],
             subject: "Hello".to_string(),
             body: "Hello, world!".to_string(),
+            ..Default::default()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mail_parser::{MessageParser, MimeHeaders};
+
+    #[test]
+    fn plain_email_should_be_single_part() -> Result<()> {
+        let email = EmailMessage::fake();
+        let mime = email.to_mime()?.formatted();
+        let parsed = MessageParser::default().parse(&mime).unwrap();
+
+        assert_eq!(parsed.subject(), Some("Hello"));
+        assert_eq!(
+            parsed.message_id(),
+            Some(format!("{}@crm-send", email.message_id).as_str())
+        );
+        assert_eq!(parsed.body_text(0).unwrap().trim_end(), "Hello, world!");
+        assert_eq!(parsed.parts.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn rich_email_should_build_multipart_mime() -> Result<()> {
+        let mut email = EmailMessage::fake();
+        email.html_body = r#"<p>Hello</p><img src="cid:logo">"#.to_string();
+        email.cc = vec!["cc@acme.org".to_string()];
+        email.bcc = vec!["bcc@acme.org".to_string()];
+        email.reply_to = "support@acme.org".to_string();
+        email.headers = [(
+            "List-Unsubscribe".to_string(),
+            "<https://acme.org/unsubscribe>".to_string(),
+        )]
+        .into();
+        email.attachments = vec![
+            Attachment {
+                filename: "logo.png".to_string(),
+                content_type: "image/png".to_string(),
+                content: vec![0x89, b'P', b'N', b'G'],
+                content_id: "logo".to_string(),
+            },
+            Attachment {
+                filename: "report.csv".to_string(),
+                content_type: "text/csv".to_string(),
+                content: b"id,name\n1,acme\n".to_vec(),
+                content_id: String::new(),
+            },
+        ];
+
+        let msg = email.to_mime()?;
+        // bcc is only kept in the envelope
+        assert_eq!(msg.envelope().to().len(), 3);
+        let mime = msg.formatted();
+        let parsed = MessageParser::default().parse(&mime).unwrap();
+
+        assert_eq!(
+            parsed.cc().unwrap().first().unwrap().address(),
+            Some("cc@acme.org")
+        );
+        assert!(parsed.bcc().is_none());
+        assert_eq!(
+            parsed.reply_to().unwrap().first().unwrap().address(),
+            Some("support@acme.org")
+        );
+        assert_eq!(
+            parsed.header_raw("List-Unsubscribe").map(|v| v.trim()),
+            Some("<https://acme.org/unsubscribe>")
+        );
+        assert!(parsed.content_type().unwrap().c_subtype.as_deref() == Some("mixed"));
+        assert_eq!(parsed.body_text(0).unwrap().trim_end(), "Hello, world!");
+        assert!(parsed.body_html(0).unwrap().contains("cid:logo"));
+
+        assert_eq!(parsed.attachment_count(), 2);
+        let logo = parsed.attachment(0).unwrap();
+        assert_eq!(logo.content_id(), Some("logo"));
+        assert_eq!(logo.contents(), &[0x89, b'P', b'N', b'G']);
+        let report = parsed.attachment(1).unwrap();
+        assert_eq!(report.attachment_name(), Some("report.csv"));
+        assert_eq!(report.contents(), b"id,name\n1,acme\n");
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_email_should_fail_to_build() {
+        let mut email = EmailMessage::fake();
+        email.recipients = vec!["not an email".to_string()];
+        assert!(email.to_mime().is_err());
+    }
+}
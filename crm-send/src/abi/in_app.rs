@@ -2,22 +2,34 @@ use tonic::Status;
 use tracing::warn;
 
 use crate::{
-    pb::{send_request::Msg, InAppMessage, SendRequest, SendResponse},
+    pb::{send_request::Msg, DeliveryStatus, InAppMessage, SendRequest, SendResponse},
     NotificationService,
 };
 
-use super::{to_ts, Sender};
+use super::{to_ts, Job, Schedule, Sender};
 
 impl Sender for InAppMessage {
-    async fn send(self, svc: NotificationService) -> Result<SendResponse, Status> {
+    async fn send(
+        self,
+        svc: NotificationService,
+        schedule: Schedule,
+    ) -> Result<SendResponse, Status> {
         let message_id = self.message_id.clone();
-        svc.sender.send(Msg::InApp(self)).await.map_err(|e| {
+        svc.status
+            .record(&message_id, DeliveryStatus::Accepted, "")
+            .await;
+        if let Err(e) = svc.sender.send(Job::new(self, schedule)).await {
             warn!("Failed to send message: {:?}", e);
-            Status::internal("Failed to send message")
-        })?;
+            svc.status
+                .record(&message_id, DeliveryStatus::Failed, "queue closed")
+                .await;
+            return Err(Status::internal("Failed to send message"));
+        }
         Ok(SendResponse {
             message_id,
             timestamp: Some(to_ts()),
+            status: DeliveryStatus::Accepted as i32,
+            ..Default::default()
         })
     }
 }
@@ -31,7 +43,10 @@ impl From<InAppMessage> for Msg {
 impl From<InAppMessage> for SendRequest {
     fn from(in_app: InAppMessage) -> Self {
         let msg: Msg = in_app.into();
-        SendRequest { msg: Some(msg) }
+        SendRequest {
+            msg: Some(msg),
+            ..Default::default()
+        }
     }
 }
 
@@ -1,17 +1,37 @@
+mod bounce;
+mod dedupe;
+mod dispatcher;
 mod email;
 mod in_app;
+mod rate_limit;
+mod recipient;
+mod sink;
 mod sms;
+mod status;
+mod suppression;
+mod tracking;
+mod webhook;
 
-use std::{ops::Deref, sync::Arc, time::Duration};
+pub use dedupe::{Dedupe, Reservation};
+pub use dispatcher::{Dispatcher, DrainReport, Job, Schedule};
+pub use rate_limit::RateLimiter;
+pub use sink::Sink;
+pub use status::StatusStore;
+pub use suppression::Suppression;
+pub use tracking::Tracker;
+pub use webhook::{verify_signature, WebhookEvent, Webhooks};
 
-use chrono::Utc;
+use std::{future::Future, ops::Deref, sync::Arc};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
 use crm_metadata::{
     pb::{Content, UnfinishedContents},
     Tpl,
 };
 use futures::{Stream, StreamExt};
 use prost_types::Timestamp;
-use tokio::{sync::mpsc, time::sleep};
+use tokio::{sync::mpsc, time::Instant};
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Response, Status};
 use tracing::{info, warn};
@@ -20,8 +40,9 @@ use uuid::Uuid;
 use crate::{
     config::AppConfig,
     pb::{
-        notification_server::NotificationServer, send_request::Msg, EmailMessage, SendRequest,
-        SendResponse,
+        notification_server::NotificationServer, send_request::Msg, send_result, DeliveryStatus,
+        EmailMessage, Priority, SendBatchRequest, SendBatchResponse, SendError, SendRequest,
+        SendResponse, SendResult,
     },
     NotificationService, NotificationServiceInner, ResponseStream, ServiceResult,
 };
@@ -29,47 +50,211 @@ use crate::{
 const CHANNEL_SIZE: usize = 1024;
 
 pub trait Sender {
-    async fn send(self, svc: NotificationService) -> Result<SendResponse, Status>;
+    async fn send(
+        self,
+        svc: NotificationService,
+        schedule: Schedule,
+    ) -> Result<SendResponse, Status>;
 }
 
 impl NotificationService {
-    pub fn new(config: AppConfig) -> Self {
-        let sender = dummy_send();
-        let inner = NotificationServiceInner { config, sender };
-        Self {
+    pub fn new(config: AppConfig) -> Result<Self> {
+        let dedupe = Dedupe::load(&config.dedupe).context("Failed to load dedupe journal")?;
+        let suppression =
+            Suppression::load(&config.suppression).context("Failed to load suppression list")?;
+        let webhooks = Webhooks::new(&config.webhooks)
+            .context("Failed to create webhook client")?
+            .start();
+        let status = StatusStore::with_outbox(&config.status, webhooks);
+        let limiter = RateLimiter::new(&config.rate_limit);
+        let tracker = config
+            .tracking
+            .clone()
+            .map(Tracker::new)
+            .transpose()
+            .context("Failed to create tracker")?
+            .map(Arc::new);
+        let sink = Sink::new(&config.sink).context("Failed to create sink")?;
+        let sender = Dispatcher::new(
+            &config.dispatch,
+            status.clone(),
+            limiter,
+            sink,
+            tracker.clone(),
+        );
+        let inner = NotificationServiceInner {
+            config,
+            sender,
+            dedupe,
+            status,
+            suppression,
+            tracker,
+        };
+        Ok(Self {
             inner: Arc::new(inner),
-        }
+        })
     }
 
     pub fn into_server(self) -> NotificationServer<Self> {
         NotificationServer::new(self)
     }
 
+    /// wait for the queued messages to be sent until the deadline, and report the ones left
+    pub async fn drain(&self, deadline: Instant) -> DrainReport {
+        self.sender.drain(deadline).await
+    }
+
+    /// send the requests of the stream concurrently. Acks come back as soon as they are ready,
+    /// unless `ordered` is set, in which case they keep the order of the requests
     pub async fn send(
         &self,
-        mut stream: impl Stream<Item = Result<SendRequest, Status>> + Send + 'static + Unpin,
+        stream: impl Stream<Item = Result<SendRequest, Status>> + Send + 'static + Unpin,
+        ordered: bool,
     ) -> ServiceResult<ResponseStream> {
         let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+        let concurrency = self.config.dispatch.concurrency.max(1);
         let notif = self.clone();
+        let pending = stream.map(move |req| notif.spawn_handle(req));
+        let mut results: ResponseStream = if ordered {
+            Box::pin(pending.buffered(concurrency))
+        } else {
+            Box::pin(pending.buffer_unordered(concurrency))
+        };
 
         tokio::spawn(async move {
-            while let Some(Ok(req)) = stream.next().await {
-                let notif_clone = notif.clone();
-                let res = match req.msg {
-                    Some(Msg::Email(email)) => email.send(notif_clone).await,
-                    Some(Msg::Sms(sms)) => sms.send(notif_clone).await,
-                    Some(Msg::InApp(in_app)) => in_app.send(notif_clone).await,
-                    None => {
-                        warn!("Invalid request");
-                        Err(Status::invalid_argument("Invalid request"))
+            loop {
+                let res = tokio::select! {
+                    _ = tx.closed() => {
+                        info!("Client disconnected, stop sending");
+                        break;
                     }
+                    res = results.next() => match res {
+                        Some(res) => res,
+                        None => break,
+                    },
                 };
-                tx.send(res).await.unwrap();
+
+                if tx.send(res).await.is_err() {
+                    info!("Client disconnected, stop sending");
+                    break;
+                }
             }
         });
         let stream = ReceiverStream::new(rx);
         Ok(Response::new(Box::pin(stream)))
     }
+
+    /// send the requests of the batch concurrently, and return their results in request order
+    pub async fn send_batch(&self, req: SendBatchRequest) -> ServiceResult<SendBatchResponse> {
+        let max = self.config.dispatch.max_batch_size;
+        if req.requests.len() > max {
+            return Err(Status::invalid_argument(format!(
+                "Too many requests in batch: {}, max {}",
+                req.requests.len(),
+                max
+            )));
+        }
+
+        let concurrency = self.config.dispatch.concurrency.max(1);
+        let results = futures::stream::iter(req.requests)
+            .map(|req| self.spawn_handle(Ok(req)))
+            .buffered(concurrency)
+            .map(SendResult::from)
+            .collect()
+            .await;
+        Ok(Response::new(SendBatchResponse { results }))
+    }
+
+    /// handle the request in its own task, so it completes even if the client goes away
+    fn spawn_handle(
+        &self,
+        req: Result<SendRequest, Status>,
+    ) -> impl Future<Output = Result<SendResponse, Status>> {
+        let handle = tokio::spawn(self.clone().handle(req));
+        async move {
+            match handle.await {
+                Ok(res) => res,
+                Err(e) => {
+                    warn!("Failed to handle request: {:?}", e);
+                    Err(Status::internal("Failed to handle request"))
+                }
+            }
+        }
+    }
+
+    async fn handle(self, req: Result<SendRequest, Status>) -> Result<SendResponse, Status> {
+        match req {
+            Ok(SendRequest {
+                msg: Some(msg),
+                send_at,
+                expires_at,
+                priority,
+            }) => {
+                let schedule = Schedule::try_new(send_at, expires_at, priority)
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?;
+                self.dispatch(msg, schedule).await
+            }
+            Ok(SendRequest { msg: None, .. }) => {
+                warn!("Invalid request");
+                Err(Status::invalid_argument("Invalid request"))
+            }
+            Err(e) => {
+                warn!("Failed to receive request: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// send a message unless the same message_id was already sent within the dedupe ttl
+    async fn dispatch(&self, mut msg: Msg, schedule: Schedule) -> Result<SendResponse, Status> {
+        let message_id = msg.message_id().to_string();
+        let mut invalid_recipients = msg.validate_recipients();
+        invalid_recipients.extend(self.suppression.filter(&mut msg));
+        if !msg.has_recipients() {
+            warn!("Message {} has no valid recipients", message_id);
+            self.status
+                .record(&message_id, DeliveryStatus::Failed, "no valid recipients")
+                .await;
+            return Ok(SendResponse {
+                message_id,
+                timestamp: Some(to_ts()),
+                status: DeliveryStatus::Failed as i32,
+                invalid_recipients,
+            });
+        }
+
+        match self.dedupe.reserve(&message_id) {
+            Reservation::New => {}
+            Reservation::Sent(res) => {
+                info!("Duplicate message {}, skip sending", message_id);
+                return Ok(res);
+            }
+            Reservation::InFlight => {
+                info!("Duplicate message {} is still being sent", message_id);
+                return Err(Status::aborted(format!(
+                    "Message {} is in flight, retry later",
+                    message_id
+                )));
+            }
+        }
+
+        let notif = self.clone();
+        let res = match msg {
+            Msg::Email(email) => email.send(notif, schedule).await,
+            Msg::Sms(sms) => sms.send(notif, schedule).await,
+            Msg::InApp(in_app) => in_app.send(notif, schedule).await,
+        };
+        match res {
+            Ok(res) => Ok(self.dedupe.commit(SendResponse {
+                invalid_recipients,
+                ..res
+            })),
+            Err(e) => {
+                self.dedupe.release(&message_id);
+                Err(e)
+            }
+        }
+    }
 }
 
 impl Deref for NotificationService {
@@ -80,8 +265,51 @@ impl Deref for NotificationService {
     }
 }
 
+impl Msg {
+    pub fn message_id(&self) -> &str {
+        match self {
+            Msg::Email(email) => &email.message_id,
+            Msg::Sms(sms) => &sms.message_id,
+            Msg::InApp(in_app) => &in_app.message_id,
+        }
+    }
+
+    pub fn channel(&self) -> &'static str {
+        match self {
+            Msg::Email(_) => "email",
+            Msg::Sms(_) => "sms",
+            Msg::InApp(_) => "in_app",
+        }
+    }
+
+    pub fn recipients(&self) -> &[String] {
+        match self {
+            Msg::Email(email) => &email.recipients,
+            Msg::Sms(sms) => &sms.recipients,
+            Msg::InApp(_) => &[],
+        }
+    }
+}
+
+impl From<Result<SendResponse, Status>> for SendResult {
+    fn from(res: Result<SendResponse, Status>) -> Self {
+        let result = match res {
+            Ok(res) => send_result::Result::Response(res),
+            Err(e) => send_result::Result::Error(SendError {
+                code: e.code() as i32,
+                message: e.message().to_string(),
+            }),
+        };
+        SendResult {
+            result: Some(result),
+        }
+    }
+}
+
 impl SendRequest {
+    /// an email of the contents, in the campaign the engagement is tracked by
     pub fn new(
+        campaign: String,
         subject: String,
         sender: String,
         recipients: &[String],
@@ -90,16 +318,24 @@ impl SendRequest {
         let tpl = Tpl(contents);
         let msg = Msg::Email(EmailMessage {
             message_id: Uuid::new_v4().to_string(),
+            campaign,
             subject,
             sender,
             recipients: recipients.to_vec(),
             body: tpl.to_body(),
+            html_body: tpl.to_html(),
+            ..Default::default()
         });
 
-        SendRequest { msg: Some(msg) }
+        SendRequest {
+            msg: Some(msg),
+            priority: Priority::Low as i32,
+            ..Default::default()
+        }
     }
 
     pub fn new_remind(
+        campaign: String,
         subject: String,
         sender: String,
         recipients: &[String],
@@ -110,24 +346,46 @@ impl SendRequest {
 
         let msg = Msg::Email(EmailMessage {
             message_id: Uuid::new_v4().to_string(),
+            campaign,
             subject,
             sender,
             recipients: recipients.to_vec(),
             body: contents.to_string(),
+            ..Default::default()
         });
-        SendRequest { msg: Some(msg) }
+        SendRequest {
+            msg: Some(msg),
+            priority: Priority::Low as i32,
+            ..Default::default()
+        }
     }
 }
 
-fn dummy_send() -> mpsc::Sender<Msg> {
-    let (tx, mut rx) = mpsc::channel(CHANNEL_SIZE);
-    tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            info!("Sending message: {:?}", msg);
-            sleep(Duration::from_secs(1)).await;
+impl Schedule {
+    pub fn try_new(
+        send_at: Option<Timestamp>,
+        expires_at: Option<Timestamp>,
+        priority: i32,
+    ) -> Result<Self> {
+        let schedule = Self {
+            send_at: send_at.map(ts_to_utc).transpose()?,
+            expires_at: expires_at.map(ts_to_utc).transpose()?,
+            priority: Priority::try_from(priority)
+                .map_err(|_| anyhow!("Invalid priority: {}", priority))?,
+        };
+        if let (Some(send_at), Some(expires_at)) = (schedule.send_at, schedule.expires_at) {
+            if expires_at <= send_at {
+                bail!("expires_at must be after send_at");
+            }
         }
-    });
-    tx
+        Ok(schedule)
+    }
+}
+
+fn ts_to_utc(ts: Timestamp) -> Result<DateTime<Utc>> {
+    Utc.timestamp_opt(ts.seconds, ts.nanos as _)
+        .single()
+        .ok_or_else(|| anyhow!("Invalid timestamp: {:?}", ts))
 }
 
 fn to_ts() -> Timestamp {
@@ -142,25 +400,340 @@ fn to_ts() -> Timestamp {
 mod tests {
     use super::*;
     use crate::{
-        pb::{EmailMessage, InAppMessage, SmsMessage},
+        pb::{EmailMessage, GetStatusRequest, InAppMessage, SmsMessage, WatchStatusRequest},
         AppConfig,
     };
     use anyhow::Result;
+    use std::time::Duration;
+    use tokio::time::sleep;
 
     #[tokio::test]
     async fn send_should_work() -> Result<()> {
-        let config = AppConfig::load()?;
-        let service = NotificationService::new(config);
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
         let stream = tokio_stream::iter(vec![
             Ok(EmailMessage::fake().into()),
             Ok(SmsMessage::fake().into()),
             Ok(InAppMessage::fake().into()),
         ]);
 
-        let response = service.send(stream).await?;
+        let response = service.send(stream, false).await?;
         let ret = response.into_inner().collect::<Vec<_>>().await;
         assert_eq!(ret.len(), 3);
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn send_should_keep_request_order_if_ordered() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let reqs: Vec<SendRequest> = (0..20)
+            .map(|i| match i % 3 {
+                0 => EmailMessage::fake().into(),
+                1 => SmsMessage::fake().into(),
+                _ => InAppMessage::fake().into(),
+            })
+            .collect();
+        let ids: Vec<_> = reqs
+            .iter()
+            .map(|req| req.msg.as_ref().unwrap().message_id().to_string())
+            .collect();
+
+        let stream = tokio_stream::iter(reqs.clone().into_iter().map(Ok));
+        let response = service.send(stream, true).await?;
+        let ret: Vec<_> = response
+            .into_inner()
+            .map(|res| res.unwrap().message_id)
+            .collect()
+            .await;
+        assert_eq!(ret, ids);
+
+        let stream = tokio_stream::iter(reqs.into_iter().map(Ok));
+        let response = service.send(stream, false).await?;
+        let mut ret: Vec<_> = response
+            .into_inner()
+            .map(|res| res.unwrap().message_id)
+            .collect()
+            .await;
+        ret.sort();
+        let mut ids = ids;
+        ids.sort();
+        assert_eq!(ret, ids);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_should_keep_processing_after_invalid_item() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let stream = tokio_stream::iter(vec![
+            Ok(EmailMessage::fake().into()),
+            Err(Status::invalid_argument("bad request")),
+            Ok(SendRequest::default()),
+            Ok(SmsMessage::fake().into()),
+        ]);
+
+        let response = service.send(stream, true).await?;
+        let ret = response.into_inner().collect::<Vec<_>>().await;
+        assert_eq!(ret.len(), 4);
+        assert!(ret[0].is_ok());
+        assert_eq!(ret[1].as_ref().unwrap_err().message(), "bad request");
+        assert!(ret[2].is_err());
+        assert!(ret[3].is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_should_stop_when_client_disconnects() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let (req_tx, req_rx) = mpsc::channel(CHANNEL_SIZE);
+        let stream = ReceiverStream::new(req_rx).map(Ok);
+
+        let mut response = service.send(stream, false).await?.into_inner();
+        let first = EmailMessage::fake();
+        req_tx.send(first.clone().into()).await?;
+        let res = response.next().await.unwrap()?;
+        assert_eq!(res.message_id, first.message_id);
+
+        drop(response);
+        sleep(Duration::from_millis(10)).await;
+        // the request stream is dropped along with the task
+        assert!(req_tx.is_closed());
+
+        let second = EmailMessage::fake();
+        assert!(req_tx.send(second.clone().into()).await.is_err());
+        let ret = service
+            .get_status(GetStatusRequest {
+                message_id: second.message_id,
+            })
+            .await;
+        assert_eq!(ret.unwrap_err().code(), tonic::Code::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_should_hold_message_until_send_at() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let email = EmailMessage::fake();
+        let message_id = email.message_id.clone();
+        let req = SendRequest {
+            msg: Some(email.into()),
+            send_at: Some(after(Duration::from_millis(200))),
+            ..Default::default()
+        };
+
+        let response = service
+            .send(tokio_stream::iter(vec![Ok(req)]), false)
+            .await?;
+        let ret = response.into_inner().collect::<Vec<_>>().await;
+        assert!(ret[0].is_ok());
+
+        sleep(Duration::from_millis(50)).await;
+        let res = get_status(&service, &message_id).await?;
+        assert_eq!(res.status, DeliveryStatus::Accepted as i32);
+
+        sleep(Duration::from_millis(300)).await;
+        let res = get_status(&service, &message_id).await?;
+        assert_eq!(res.status, DeliveryStatus::Delivered as i32);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_should_drop_expired_message() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let email = EmailMessage::fake();
+        let message_id = email.message_id.clone();
+        let expires_at = Utc::now() - chrono::Duration::seconds(1);
+        let req = SendRequest {
+            msg: Some(email.into()),
+            expires_at: Some(Timestamp {
+                seconds: expires_at.timestamp(),
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        let invalid = SendRequest {
+            msg: Some(SmsMessage::fake().into()),
+            send_at: Some(after(Duration::from_secs(2))),
+            expires_at: Some(after(Duration::from_secs(1))),
+            ..Default::default()
+        };
+
+        let stream = tokio_stream::iter(vec![Ok(req), Ok(invalid)]);
+        let response = service.send(stream, true).await?;
+        let ret = response.into_inner().collect::<Vec<_>>().await;
+        assert!(ret[0].is_ok());
+        assert_eq!(
+            ret[1].as_ref().unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+
+        sleep(Duration::from_millis(50)).await;
+        let res = get_status(&service, &message_id).await?;
+        assert_eq!(res.status, DeliveryStatus::Expired as i32);
+
+        Ok(())
+    }
+
+    async fn get_status(
+        service: &NotificationService,
+        message_id: &str,
+    ) -> Result<crate::pb::GetStatusResponse> {
+        let req = GetStatusRequest {
+            message_id: message_id.to_string(),
+        };
+        Ok(service.get_status(req).await?.into_inner())
+    }
+
+    fn after(duration: Duration) -> Timestamp {
+        let dt = Utc::now() + duration;
+        Timestamp {
+            seconds: dt.timestamp(),
+            nanos: dt.timestamp_subsec_nanos() as i32,
+        }
+    }
+
+    #[tokio::test]
+    async fn status_should_be_tracked_after_send() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let email = EmailMessage::fake();
+        let message_id = email.message_id.clone();
+
+        let mut events = service
+            .watch_status(WatchStatusRequest {
+                message_ids: vec![message_id.clone()],
+            })
+            .await?
+            .into_inner();
+
+        let stream = tokio_stream::iter(vec![Ok(email.into())]);
+        let response = service.send(stream, false).await?;
+        let ret = response.into_inner().collect::<Vec<_>>().await;
+        assert_eq!(ret.len(), 1);
+
+        let event = events.next().await.unwrap()?;
+        assert_eq!(event.message_id, message_id);
+        assert_eq!(event.status, DeliveryStatus::Accepted as i32);
+        let event = events.next().await.unwrap()?;
+        assert_eq!(event.status, DeliveryStatus::Delivered as i32);
+
+        let res = service
+            .get_status(GetStatusRequest { message_id })
+            .await?
+            .into_inner();
+        assert_eq!(res.status, DeliveryStatus::Delivered as i32);
+        assert_eq!(res.events.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_should_skip_duplicate_message_id() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let email = EmailMessage::fake();
+        let message_id = email.message_id.clone();
+
+        let stream = tokio_stream::iter(vec![Ok(email.clone().into()), Ok(email.into())]);
+        let response = service.send(stream, false).await?;
+        let ret = response
+            .into_inner()
+            .map(|res| res.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(ret.len(), 2);
+        assert_eq!(ret[0], ret[1]);
+
+        let res = service
+            .get_status(GetStatusRequest { message_id })
+            .await?
+            .into_inner();
+        let accepted = res
+            .events
+            .iter()
+            .filter(|e| e.status == DeliveryStatus::Accepted as i32)
+            .count();
+        assert_eq!(accepted, 1);
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn send_should_report_invalid_recipients() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let mut partial = EmailMessage::fake();
+        partial.recipients = vec!["Tyr@Acme.ORG".to_string(), "not an email".to_string()];
+        let mut invalid = SmsMessage::fake();
+        invalid.recipients = vec!["12345".to_string()];
+        let invalid_id = invalid.message_id.clone();
+
+        let stream = tokio_stream::iter(vec![Ok(partial.into()), Ok(invalid.into())]);
+        let response = service.send(stream, true).await?;
+        let ret = response
+            .into_inner()
+            .map(|res| res.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(ret[0].status, DeliveryStatus::Accepted as i32);
+        assert_eq!(ret[0].invalid_recipients.len(), 1);
+        assert_eq!(ret[0].invalid_recipients[0].recipient, "not an email");
+
+        assert_eq!(ret[1].status, DeliveryStatus::Failed as i32);
+        assert_eq!(ret[1].invalid_recipients[0].recipient, "12345");
+        assert_eq!(ret[1].invalid_recipients[0].reason, "missing country code");
+        let res = get_status(&service, &invalid_id).await?;
+        assert_eq!(res.status, DeliveryStatus::Failed as i32);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_batch_should_return_results_in_order() -> Result<()> {
+        let config = AppConfig::load_for_test()?;
+        let service = NotificationService::new(config)?;
+        let email = EmailMessage::fake();
+        let mut invalid = SmsMessage::fake();
+        invalid.recipients = vec!["12345".to_string()];
+        let req = SendBatchRequest {
+            requests: vec![
+                email.clone().into(),
+                SendRequest::default(),
+                invalid.clone().into(),
+            ],
+        };
+
+        let res = service.send_batch(req).await?.into_inner();
+        assert_eq!(res.results.len(), 3);
+        let Some(send_result::Result::Response(ref res0)) = res.results[0].result else {
+            panic!("expect a response");
+        };
+        assert_eq!(res0.message_id, email.message_id);
+        let Some(send_result::Result::Error(ref err)) = res.results[1].result else {
+            panic!("expect an error");
+        };
+        assert_eq!(err.code, tonic::Code::InvalidArgument as i32);
+        let Some(send_result::Result::Response(ref res2)) = res.results[2].result else {
+            panic!("expect a response");
+        };
+        assert_eq!(res2.message_id, invalid.message_id);
+        assert_eq!(res2.status, DeliveryStatus::Failed as i32);
+
+        let req = SendBatchRequest {
+            requests: vec![SendRequest::default(); 1001],
+        };
+        let ret = service.send_batch(req).await;
+        assert_eq!(ret.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        Ok(())
+    }
 }
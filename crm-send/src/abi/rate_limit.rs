@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::{
+    config::{BucketConfig, RateLimitConfig},
+    pb::send_request::Msg,
+};
+
+/// token bucket limits per channel and per recipient domain. A throttled message is put
+/// back to wait in its queue instead of being rejected, so it doesn't hold a worker
+pub struct RateLimiter {
+    channels: HashMap<String, Mutex<TokenBucket>>,
+    domains: HashMap<String, Mutex<TokenBucket>>,
+}
+
+struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        let buckets = |limits: &HashMap<String, BucketConfig>| {
+            limits
+                .iter()
+                .map(|(k, v)| (k.to_lowercase(), Mutex::new(TokenBucket::new(v))))
+                .collect()
+        };
+        Self {
+            channels: buckets(&config.channels),
+            domains: buckets(&config.domains),
+        }
+    }
+
+    /// take a token from the channel and all the recipient domains of the message, or none
+    /// of them and return how long to wait before trying again
+    pub fn try_acquire(&self, msg: &Msg) -> Option<Duration> {
+        let mut domains: Vec<_> = msg
+            .recipients()
+            .iter()
+            .filter_map(|r| domain_of(r))
+            .collect();
+        // always lock in the same order, so workers don't deadlock
+        domains.sort();
+        domains.dedup();
+
+        let channel = msg.channel();
+        let mut buckets: Vec<(&'static str, &str, MutexGuard<TokenBucket>)> = Vec::new();
+        if let Some(bucket) = self.channels.get(channel) {
+            buckets.push(("channel", channel, bucket.lock().unwrap()));
+        }
+        for domain in &domains {
+            if let Some(bucket) = self.domains.get(domain) {
+                buckets.push(("domain", domain, bucket.lock().unwrap()));
+            }
+        }
+
+        let mut wait = None;
+        for (kind, key, bucket) in buckets.iter_mut() {
+            if let Some(w) = bucket.wait() {
+                debug!("Throttled by {} {} for {:?}", kind, key, w);
+                let labels = [("kind", kind.to_string()), ("key", key.to_string())];
+                metrics::counter!("crm_send_throttled_total", &labels).increment(1);
+                metrics::histogram!("crm_send_throttle_wait_seconds", &labels)
+                    .record(w.as_secs_f64());
+                wait = wait.max(Some(w));
+            }
+        }
+        if wait.is_none() {
+            for (_, _, bucket) in buckets.iter_mut() {
+                bucket.take();
+            }
+        }
+        wait
+    }
+}
+
+impl TokenBucket {
+    fn new(config: &BucketConfig) -> Self {
+        let burst = config.burst.max(1) as f64;
+        Self {
+            rate: config.rate,
+            burst,
+            tokens: burst,
+            last: Instant::now(),
+        }
+    }
+
+    /// refill the bucket, and return how long to wait for the next token if there's none
+    fn wait(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last = now;
+
+        if self.tokens >= 1.0 || self.rate <= 0.0 {
+            // a bucket without refill would block forever, treat it as unlimited
+            return None;
+        }
+        Some(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+    }
+
+    fn take(&mut self) {
+        self.tokens = (self.tokens - 1.0).max(0.0);
+    }
+}
+
+fn domain_of(recipient: &str) -> Option<String> {
+    recipient
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::EmailMessage;
+
+    #[tokio::test]
+    async fn rate_limiter_should_throttle_by_domain() {
+        let config = RateLimitConfig {
+            channels: HashMap::new(),
+            domains: [(
+                "gmail.com".to_string(),
+                BucketConfig {
+                    rate: 20.0,
+                    burst: 1,
+                },
+            )]
+            .into(),
+        };
+        let limiter = RateLimiter::new(&config);
+        let mut email = EmailMessage::fake();
+        let other = Msg::Email(email.clone());
+        email.recipients = vec!["a@Gmail.com".to_string()];
+        let gmail = Msg::Email(email);
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire(&other).is_none());
+        }
+
+        assert!(limiter.try_acquire(&gmail).is_none());
+        let wait = limiter.try_acquire(&gmail).unwrap();
+        assert!(wait > Duration::from_millis(40) && wait <= Duration::from_millis(50));
+        // a throttled message takes no token, so it's allowed once the wait is over
+        tokio::time::sleep(wait).await;
+        assert!(limiter.try_acquire(&gmail).is_none());
+        assert!(limiter.try_acquire(&gmail).is_some());
+    }
+}
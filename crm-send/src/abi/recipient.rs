@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+use lettre::Address;
+
+use crate::pb::{send_request::Msg, RecipientError};
+
+impl Msg {
+    /// normalize the recipients in place, dropping the duplicates and the invalid ones.
+    /// Returns the invalid recipients along with the reason they were dropped. The cc, bcc
+    /// and reply_to of emails are normalized the same way, but are optional
+    pub fn validate_recipients(&mut self) -> Vec<RecipientError> {
+        let channel = self.channel();
+        let errors = match self {
+            Msg::Email(email) => {
+                let mut errors = normalize_required(&mut email.recipients, normalize_email);
+                errors.extend(normalize_all(&mut email.cc, normalize_email));
+                errors.extend(normalize_all(&mut email.bcc, normalize_email));
+                if !email.reply_to.trim().is_empty() {
+                    let mut reply_to = vec![std::mem::take(&mut email.reply_to)];
+                    errors.extend(normalize_all(&mut reply_to, normalize_email));
+                    // an invalid reply_to is dropped, so replies go to the sender
+                    email.reply_to = reply_to.pop().unwrap_or_default();
+                }
+                errors
+            }
+            Msg::Sms(sms) => normalize_required(&mut sms.recipients, normalize_phone),
+            Msg::InApp(in_app) => {
+                in_app.device_id = in_app.device_id.trim().to_string();
+                if in_app.device_id.is_empty() {
+                    vec![invalid("", "device_id is empty")]
+                } else {
+                    vec![]
+                }
+            }
+        };
+
+        if !errors.is_empty() {
+            metrics::counter!("crm_send_invalid_recipients_total", "channel" => channel)
+                .increment(errors.len() as u64);
+        }
+        errors
+    }
+
+    /// a message without any valid recipient is not worth sending
+    pub fn has_recipients(&self) -> bool {
+        match self {
+            Msg::Email(email) => !email.recipients.is_empty(),
+            Msg::Sms(sms) => !sms.recipients.is_empty(),
+            Msg::InApp(in_app) => !in_app.device_id.is_empty(),
+        }
+    }
+}
+
+/// normalize an email address or a phone number, e.g. the recipient of a bounce
+pub(super) fn normalize_recipient(recipient: &str) -> Result<String> {
+    if recipient.contains('@') {
+        normalize_email(recipient)
+    } else {
+        normalize_phone(recipient)
+    }
+}
+
+fn normalize_required(
+    recipients: &mut Vec<String>,
+    normalize: fn(&str) -> Result<String>,
+) -> Vec<RecipientError> {
+    if recipients.is_empty() {
+        return vec![invalid("", "no recipients")];
+    }
+    normalize_all(recipients, normalize)
+}
+
+fn normalize_all(
+    recipients: &mut Vec<String>,
+    normalize: fn(&str) -> Result<String>,
+) -> Vec<RecipientError> {
+    let mut seen = HashSet::new();
+    let mut errors = vec![];
+    let mut valid = Vec::with_capacity(recipients.len());
+    for recipient in recipients.drain(..) {
+        match normalize(&recipient) {
+            Ok(normalized) => {
+                if seen.insert(normalized.clone()) {
+                    valid.push(normalized);
+                }
+            }
+            Err(e) => errors.push(invalid(&recipient, &e.to_string())),
+        }
+    }
+    *recipients = valid;
+    errors
+}
+
+/// lower-case the domain and convert it to its ascii (punycode) form. The local part is
+/// kept as is, since it's case sensitive by the spec even if most servers ignore the case
+fn normalize_email(email: &str) -> Result<String> {
+    let Some((local, domain)) = email.trim().rsplit_once('@') else {
+        bail!("missing @");
+    };
+    if local.is_empty() {
+        bail!("empty local part");
+    }
+    let domain = idna::domain_to_ascii(domain).context("invalid domain")?;
+    if !domain.contains('.') {
+        bail!("invalid domain");
+    }
+
+    let email = format!("{}@{}", local, domain);
+    email.parse::<Address>().context("invalid email address")?;
+    Ok(email)
+}
+
+/// convert the phone number to E.164: a plus sign and up to 15 digits, starting with the
+/// country code. Common separators are allowed, and 00 is accepted as the international prefix
+fn normalize_phone(phone: &str) -> Result<String> {
+    let phone = phone.trim();
+    let Some(number) = phone.strip_prefix('+').or_else(|| phone.strip_prefix("00")) else {
+        bail!("missing country code");
+    };
+
+    let mut digits = String::with_capacity(number.len());
+    for c in number.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '.' | '(' | ')' => {}
+            _ => bail!("invalid character {:?}", c),
+        }
+    }
+    if digits.starts_with('0') || !(8..=15).contains(&digits.len()) {
+        bail!("not a valid E.164 number");
+    }
+    Ok(format!("+{}", digits))
+}
+
+fn invalid(recipient: &str, reason: &str) -> RecipientError {
+    RecipientError {
+        recipient: recipient.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::{EmailMessage, InAppMessage, SmsMessage};
+
+    #[test]
+    fn email_recipients_should_be_normalized() {
+        let mut email = EmailMessage::fake();
+        email.recipients = vec![
+            "Tyr@Example.COM".to_string(),
+            " tyr@example.com ".to_string(),
+            "user@bücher.de".to_string(),
+            "not an email".to_string(),
+            "a b@example.com".to_string(),
+            "tyr@localhost".to_string(),
+        ];
+        let mut msg = Msg::Email(email);
+        let errors = msg.validate_recipients();
+
+        assert_eq!(
+            msg.recipients(),
+            [
+                "Tyr@example.com",
+                "tyr@example.com",
+                "user@xn--bcher-kva.de"
+            ]
+        );
+        let invalid: Vec<_> = errors.iter().map(|e| e.recipient.as_str()).collect();
+        assert_eq!(
+            invalid,
+            ["not an email", "a b@example.com", "tyr@localhost"]
+        );
+        assert_eq!(errors[0].reason, "missing @");
+    }
+
+    #[test]
+    fn email_cc_bcc_and_reply_to_should_be_normalized() {
+        let mut email = EmailMessage::fake();
+        email.cc = vec!["a@Example.COM".to_string(), "cc".to_string()];
+        email.bcc = vec!["b@bücher.de".to_string(), "b@bücher.de".to_string()];
+        email.reply_to = " reply@Example.com ".to_string();
+        let mut msg = Msg::Email(email);
+        let errors = msg.validate_recipients();
+        let Msg::Email(email) = &msg else {
+            unreachable!()
+        };
+        assert_eq!(email.cc, ["a@example.com"]);
+        assert_eq!(email.bcc, ["b@xn--bcher-kva.de"]);
+        assert_eq!(email.reply_to, "reply@example.com");
+        assert_eq!(errors, [invalid("cc", "missing @")]);
+
+        let mut email = email.clone();
+        email.cc = vec![];
+        email.reply_to = "reply@localhost".to_string();
+        let mut msg = Msg::Email(email);
+        let errors = msg.validate_recipients();
+        assert_eq!(errors, [invalid("reply@localhost", "invalid domain")]);
+        let Msg::Email(email) = &msg else {
+            unreachable!()
+        };
+        assert!(email.reply_to.is_empty());
+        assert!(msg.has_recipients());
+    }
+
+    #[test]
+    fn sms_recipients_should_be_normalized() {
+        let mut sms = SmsMessage::fake();
+        sms.recipients = vec![
+            "+1 (415) 555-2671".to_string(),
+            "0014155552671".to_string(),
+            "+44 20 7946 0958".to_string(),
+            "415-555-2671".to_string(),
+            "+1 415 CALL ME".to_string(),
+            "+0123456789".to_string(),
+        ];
+        let mut msg = Msg::Sms(sms);
+        let errors = msg.validate_recipients();
+
+        assert_eq!(msg.recipients(), ["+14155552671", "+442079460958"]);
+        let reasons: Vec<_> = errors.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            [
+                "missing country code",
+                "invalid character 'C'",
+                "not a valid E.164 number"
+            ]
+        );
+    }
+
+    #[test]
+    fn message_without_recipients_should_be_rejected() {
+        let mut sms = SmsMessage::fake();
+        sms.recipients = vec![];
+        let mut msg = Msg::Sms(sms);
+        assert_eq!(msg.validate_recipients().len(), 1);
+        assert!(!msg.has_recipients());
+
+        let mut in_app = InAppMessage::fake();
+        in_app.device_id = " ".to_string();
+        let mut msg = Msg::InApp(in_app);
+        assert_eq!(msg.validate_recipients()[0].reason, "device_id is empty");
+        assert!(!msg.has_recipients());
+    }
+}
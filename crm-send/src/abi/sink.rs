@@ -0,0 +1,235 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex, time::sleep};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::{EmailFormat, SinkConfig},
+    pb::{send_request::Msg, DeliveryStatus, EmailMessage},
+};
+
+use super::{StatusStore, Tracker};
+
+/// where the dispatcher delivers the messages to
+pub enum Sink {
+    /// log the messages, with a simulated latency
+    Log,
+    /// write the messages to files, so what's sent could be looked at
+    File(FileSink),
+}
+
+pub struct FileSink {
+    dir: PathBuf,
+    email_format: EmailFormat,
+    // appends from concurrent workers must not interleave
+    lock: Mutex<()>,
+}
+
+/// a line of the sms and in-app jsonl files
+#[derive(Serialize)]
+struct SinkRecord<'a, T> {
+    sent_at: DateTime<Utc>,
+    #[serde(flatten)]
+    message: &'a T,
+}
+
+impl Sink {
+    pub fn new(config: &SinkConfig) -> Result<Self> {
+        match config {
+            SinkConfig::Log => Ok(Sink::Log),
+            SinkConfig::File { dir, email_format } => {
+                std::fs::create_dir_all(dir.join("email"))
+                    .context(format!("Failed to create sink dir: {:?}", dir))?;
+                info!("Writing messages to {:?}", dir);
+                Ok(Sink::File(FileSink {
+                    dir: dir.clone(),
+                    email_format: *email_format,
+                    lock: Mutex::new(()),
+                }))
+            }
+        }
+    }
+
+    /// deliver the message and record the outcome
+    pub async fn send(&self, msg: Msg, status: &StatusStore, tracker: Option<&Tracker>) {
+        let ret = match self {
+            Sink::Log => {
+                if let Msg::Email(email) = &msg {
+                    match email.to_mime() {
+                        Ok(mime) => debug!(
+                            "Rendered email {}:\n{}",
+                            email.message_id,
+                            String::from_utf8_lossy(&mime.formatted())
+                        ),
+                        Err(e) => {
+                            fail(&msg, status, e).await;
+                            return;
+                        }
+                    }
+                }
+                info!("Sending message: {:?}", msg);
+                delivered(&msg, status, tracker).await;
+                sleep(Duration::from_secs(1)).await;
+                return;
+            }
+            Sink::File(sink) => sink.write(&msg).await,
+        };
+
+        match ret {
+            Ok(()) => delivered(&msg, status, tracker).await,
+            Err(e) => fail(&msg, status, e).await,
+        }
+    }
+}
+
+impl FileSink {
+    async fn write(&self, msg: &Msg) -> Result<()> {
+        match msg {
+            Msg::Email(email) => self.write_email(email).await,
+            Msg::Sms(sms) => self.append_jsonl("sms.jsonl", sms).await,
+            Msg::InApp(in_app) => self.append_jsonl("in_app.jsonl", in_app).await,
+        }
+    }
+
+    async fn write_email(&self, email: &EmailMessage) -> Result<()> {
+        let mime = email.to_mime()?.formatted();
+        match self.email_format {
+            EmailFormat::Eml => {
+                validate_file_name(&email.message_id)?;
+                let path = self
+                    .dir
+                    .join("email")
+                    .join(format!("{}.eml", email.message_id));
+                fs::write(&path, mime)
+                    .await
+                    .context(format!("Failed to write {:?}", path))
+            }
+            EmailFormat::Mbox => {
+                let sender = if email.sender.is_empty() {
+                    "MAILER-DAEMON"
+                } else {
+                    &email.sender
+                };
+                let from_line =
+                    format!("From {} {}\n", sender, Utc::now().format("%a %b %e %T %Y"));
+                let mut entry = from_line.into_bytes();
+                entry.extend(to_mboxrd(&mime));
+                self.append("email.mbox", &entry).await
+            }
+        }
+    }
+
+    async fn append_jsonl<T: Serialize>(&self, name: &str, message: &T) -> Result<()> {
+        let record = SinkRecord {
+            sent_at: Utc::now(),
+            message,
+        };
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        self.append(name, &line).await
+    }
+
+    async fn append(&self, name: &str, data: &[u8]) -> Result<()> {
+        let path = self.dir.join(name);
+        let _guard = self.lock.lock().await;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .context(format!("Failed to open {:?}", path))?;
+        file.write_all(data)
+            .await
+            .context(format!("Failed to write {:?}", path))?;
+        // tokio writes in the background, make sure it's done before delivery is recorded
+        file.flush()
+            .await
+            .context(format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+}
+
+/// convert the message to mboxrd: unix line endings, and lines starting with "From "
+/// (after any number of ">") get another ">", so they don't start a new message
+fn to_mboxrd(mime: &[u8]) -> Vec<u8> {
+    let text = String::from_utf8_lossy(mime);
+    let mut out = Vec::with_capacity(mime.len() + 64);
+    for line in text.lines() {
+        if line.trim_start_matches('>').starts_with("From ") {
+            out.push(b'>');
+        }
+        out.extend_from_slice(line.as_bytes());
+        out.push(b'\n');
+    }
+    // a blank line ends the message
+    out.push(b'\n');
+    out
+}
+
+/// the message id names the eml file, so it must not reach outside the sink dir
+fn validate_file_name(message_id: &str) -> Result<()> {
+    let valid = !message_id.is_empty()
+        && message_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid {
+        bail!("Invalid message_id for a file name: {:?}", message_id);
+    }
+    Ok(())
+}
+
+async fn delivered(msg: &Msg, status: &StatusStore, tracker: Option<&Tracker>) {
+    if let (Msg::Email(email), Some(tracker)) = (msg, tracker) {
+        tracker.sent(email);
+    }
+    status
+        .record(msg.message_id(), DeliveryStatus::Delivered, "")
+        .await;
+}
+
+async fn fail(msg: &Msg, status: &StatusStore, e: anyhow::Error) {
+    warn!("Failed to send message {}: {:?}", msg.message_id(), e);
+    status
+        .record(msg.message_id(), DeliveryStatus::Failed, e.to_string())
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mboxrd_should_quote_from_lines() {
+        let mime = b"Subject: Hi\r\n\r\nFrom here\r\n>From there\r\nFromage\r\n";
+        assert_eq!(
+            to_mboxrd(mime),
+            b"Subject: Hi\n\n>From here\n>>From there\nFromage\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn eml_should_not_be_written_outside_sink_dir() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = SinkConfig::File {
+            dir: dir.path().join("sink"),
+            email_format: EmailFormat::Eml,
+        };
+        let Sink::File(sink) = Sink::new(&config)? else {
+            unreachable!()
+        };
+
+        let mut email = EmailMessage::fake();
+        email.message_id = "../../escaped".to_string();
+        let e = sink.write_email(&email).await.unwrap_err();
+        assert!(e.to_string().starts_with("Invalid message_id"));
+        assert!(!dir.path().join("escaped.eml").exists());
+
+        email.message_id = "a1.b_c-d".to_string();
+        sink.write_email(&email).await?;
+        assert!(dir.path().join("sink/email/a1.b_c-d.eml").exists());
+        Ok(())
+    }
+}
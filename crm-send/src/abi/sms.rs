@@ -1,23 +1,32 @@
 use tonic::Status;
 use tracing::warn;
 
-use crate::pb::{send_request::Msg, SendRequest, SendResponse, SmsMessage};
+use crate::pb::{send_request::Msg, DeliveryStatus, SendRequest, SendResponse, SmsMessage};
 
-use super::{to_ts, Sender};
+use super::{to_ts, Job, Schedule, Sender};
 
 impl Sender for SmsMessage {
     async fn send(
         self,
         svc: crate::NotificationService,
+        schedule: Schedule,
     ) -> Result<crate::pb::SendResponse, tonic::Status> {
         let message_id = self.message_id.clone();
-        svc.sender.send(Msg::Sms(self)).await.map_err(|e| {
+        svc.status
+            .record(&message_id, DeliveryStatus::Accepted, "")
+            .await;
+        if let Err(e) = svc.sender.send(Job::new(self, schedule)).await {
             warn!("Failed to send message: {:?}", e);
-            Status::internal("Failed to send message")
-        })?;
+            svc.status
+                .record(&message_id, DeliveryStatus::Failed, "queue closed")
+                .await;
+            return Err(Status::internal("Failed to send message"));
+        }
         Ok(SendResponse {
             message_id,
             timestamp: Some(to_ts()),
+            status: DeliveryStatus::Accepted as i32,
+            ..Default::default()
         })
     }
 }
@@ -31,21 +40,26 @@ impl From<SmsMessage> for Msg {
 impl From<SmsMessage> for SendRequest {
     fn from(value: SmsMessage) -> Self {
         let msg: Msg = value.into();
-        SendRequest { msg: Some(msg) }
+        SendRequest {
+            msg: Some(msg),
+            ..Default::default()
+        }
     }
 }
 
 #[cfg(feature = "test_utils")]
 impl SmsMessage {
     pub fn fake() -> Self {
-        use fake::faker::phone_number::en::PhoneNumber;
+        use fake::faker::number::en::NumberWithFormat;
         use fake::Fake;
         use uuid::Uuid;
 
+        // E.164 numbers in the north american numbering plan
+        let phone = || NumberWithFormat("+1^##^######").fake();
         SmsMessage {
             message_id: Uuid::new_v4().to_string(),
-            sender: PhoneNumber().fake(),
-            recipients: vec![PhoneNumber().fake()],
+            sender: phone(),
+            recipients: vec![phone()],
             body: "Hello, world!".to_string(),
         }
     }
@@ -0,0 +1,216 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use hashlink::LinkedHashMap;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Response, Status};
+use tracing::warn;
+
+use crate::{
+    config::StatusConfig,
+    pb::{DeliveryStatus, GetStatusRequest, GetStatusResponse, StatusEvent, WatchStatusRequest},
+    NotificationService, ServiceResult, StatusStream,
+};
+
+use super::{to_ts, CHANNEL_SIZE};
+
+/// keeps the delivery lifecycle of the recent messages and broadcasts the changes. A
+/// message is forgotten once it has no change for the ttl, or it's the least recently
+/// changed one when the store is full. Watchers may miss changes when they lag behind, the
+/// outbox never does
+#[derive(Clone)]
+pub struct StatusStore {
+    inner: Arc<StatusStoreInner>,
+}
+
+struct StatusStoreInner {
+    /// ordered from the least to the most recently changed
+    messages: RwLock<LinkedHashMap<String, Lifecycle>>,
+    ttl: Duration,
+    capacity: usize,
+    tx: broadcast::Sender<StatusEvent>,
+    /// every change is queued here too, waiting for room if it's full
+    outbox: Option<mpsc::Sender<StatusEvent>>,
+}
+
+struct Lifecycle {
+    events: Vec<StatusEvent>,
+    updated_at: Instant,
+}
+
+impl StatusStore {
+    pub fn new(config: &StatusConfig) -> Self {
+        Self::with_outbox(config, None)
+    }
+
+    pub fn with_outbox(config: &StatusConfig, outbox: Option<mpsc::Sender<StatusEvent>>) -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_SIZE);
+        let inner = StatusStoreInner {
+            messages: RwLock::new(LinkedHashMap::new()),
+            ttl: Duration::from_secs(config.ttl),
+            capacity: config.capacity.max(1),
+            tx,
+            outbox,
+        };
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
+    pub async fn record(
+        &self,
+        message_id: &str,
+        status: DeliveryStatus,
+        detail: impl Into<String>,
+    ) {
+        let event = StatusEvent {
+            message_id: message_id.to_string(),
+            status: status as i32,
+            timestamp: Some(to_ts()),
+            detail: detail.into(),
+        };
+
+        // the lock is released before waiting for room in the outbox
+        {
+            let now = Instant::now();
+            let mut messages = self.inner.messages.write().unwrap();
+            let mut lifecycle = messages.remove(&event.message_id).unwrap_or(Lifecycle {
+                events: vec![],
+                updated_at: now,
+            });
+            lifecycle.events.push(event.clone());
+            lifecycle.updated_at = now;
+            messages.insert(event.message_id.clone(), lifecycle);
+            while let Some((_, oldest)) = messages.front() {
+                let expired = now.duration_since(oldest.updated_at) >= self.inner.ttl;
+                if !expired && messages.len() <= self.inner.capacity {
+                    break;
+                }
+                messages.pop_front();
+            }
+        }
+        if let Some(outbox) = &self.inner.outbox {
+            if outbox.send(event.clone()).await.is_err() {
+                warn!(
+                    "Status outbox is closed, change of {} not queued",
+                    message_id
+                );
+            }
+        }
+        // no one is watching is not an error
+        let _ = self.inner.tx.send(event);
+    }
+
+    pub fn get(&self, message_id: &str) -> Option<GetStatusResponse> {
+        let messages = self.inner.messages.read().unwrap();
+        let lifecycle = messages.get(message_id)?;
+        if lifecycle.updated_at.elapsed() >= self.inner.ttl {
+            return None;
+        }
+        let events = &lifecycle.events;
+        let status = events.last().map(|e| e.status).unwrap_or_default();
+        Some(GetStatusResponse {
+            message_id: message_id.to_string(),
+            status,
+            events: events.clone(),
+        })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<StatusEvent> {
+        self.inner.tx.subscribe()
+    }
+}
+
+impl Default for StatusStore {
+    fn default() -> Self {
+        Self::new(&StatusConfig::default())
+    }
+}
+
+impl NotificationService {
+    pub async fn get_status(&self, req: GetStatusRequest) -> ServiceResult<GetStatusResponse> {
+        match self.status.get(&req.message_id) {
+            Some(res) => Ok(Response::new(res)),
+            None => Err(Status::not_found(format!(
+                "Message {} not found",
+                req.message_id
+            ))),
+        }
+    }
+
+    pub async fn watch_status(&self, req: WatchStatusRequest) -> ServiceResult<StatusStream> {
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+        // subscribe before the task starts, so no change is missed in between
+        let mut events = self.status.subscribe();
+        let ids = req.message_ids;
+
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("Status watcher lagged behind, {} events skipped", n);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if !ids.is_empty() && !ids.contains(&event.message_id) {
+                    continue;
+                }
+                if tx.send(Ok(event)).await.is_err() {
+                    // client is gone
+                    break;
+                }
+            }
+        });
+
+        let stream = ReceiverStream::new(rx);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn status_store_should_keep_latest_status() {
+        let store = StatusStore::default();
+        store.record("1", DeliveryStatus::Accepted, "").await;
+        store.record("1", DeliveryStatus::Delivered, "").await;
+        store.record("2", DeliveryStatus::Accepted, "").await;
+
+        let res = store.get("1").unwrap();
+        assert_eq!(res.status, DeliveryStatus::Delivered as i32);
+        assert_eq!(res.events.len(), 2);
+        assert_eq!(res.events[0].status, DeliveryStatus::Accepted as i32);
+        assert!(store.get("3").is_none());
+    }
+
+    #[tokio::test]
+    async fn status_store_should_forget_old_messages() {
+        let store = StatusStore::new(&StatusConfig {
+            ttl: 60,
+            capacity: 2,
+        });
+        store.record("1", DeliveryStatus::Accepted, "").await;
+        store.record("2", DeliveryStatus::Accepted, "").await;
+        store.record("1", DeliveryStatus::Delivered, "").await;
+        store.record("3", DeliveryStatus::Accepted, "").await;
+        // 2 is the least recently changed
+        assert!(store.get("2").is_none());
+        assert_eq!(store.get("1").unwrap().events.len(), 2);
+        assert!(store.get("3").is_some());
+
+        let store = StatusStore::new(&StatusConfig {
+            ttl: 0,
+            capacity: 10,
+        });
+        store.record("1", DeliveryStatus::Delivered, "").await;
+        assert!(store.get("1").is_none());
+        assert!(store.inner.messages.read().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    config::SuppressionConfig,
+    pb::{send_request::Msg, RecipientError},
+};
+
+/// recipients that hard bounced or complained. Messages are never sent to them again
+pub struct Suppression {
+    path: Option<PathBuf>,
+    inner: Mutex<SuppressionInner>,
+}
+
+struct SuppressionInner {
+    entries: HashMap<String, SuppressionEntry>,
+    journal: Option<File>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SuppressionEntry {
+    recipient: String,
+    reason: String,
+    created_at: DateTime<Utc>,
+}
+
+impl Suppression {
+    /// load the suppressed recipients from the journal
+    pub fn load(config: &SuppressionConfig) -> Result<Self> {
+        let mut entries = HashMap::new();
+        let mut journal = None;
+
+        if let Some(path) = &config.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .context(format!("Failed to create suppression dir: {:?}", parent))?;
+            }
+
+            if let Ok(file) = File::open(path) {
+                for line in BufReader::new(file).lines() {
+                    let line = line.context("Failed to read suppression journal")?;
+                    match serde_json::from_str::<SuppressionEntry>(&line) {
+                        Ok(entry) => {
+                            entries.insert(key(&entry.recipient), entry);
+                        }
+                        Err(e) => warn!("Skip invalid suppression entry {:?}: {:?}", line, e),
+                    }
+                }
+            }
+
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .context(format!("Failed to open {:?}", path))?;
+            journal = Some(file);
+        }
+
+        Ok(Self {
+            path: config.path.clone(),
+            inner: Mutex::new(SuppressionInner { entries, journal }),
+        })
+    }
+
+    /// suppress the (normalized) recipient; returns false if it's already suppressed
+    pub fn add(&self, recipient: &str, reason: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.entries.contains_key(&key(recipient)) {
+            return false;
+        }
+
+        let entry = SuppressionEntry {
+            recipient: recipient.to_string(),
+            reason: reason.to_string(),
+            created_at: Utc::now(),
+        };
+        if let Some(journal) = inner.journal.as_mut() {
+            let ret = serde_json::to_string(&entry)
+                .map_err(anyhow::Error::from)
+                .and_then(|line| Ok(writeln!(journal, "{}", line)?));
+            if let Err(e) = ret {
+                warn!(
+                    "Failed to write suppression journal {:?}: {:?}",
+                    self.path, e
+                );
+            }
+        }
+        info!("Suppress {}: {}", recipient, reason);
+        inner.entries.insert(key(&entry.recipient), entry);
+        true
+    }
+
+    /// drop the suppressed recipients from the message, cc and bcc included, and return
+    /// them as invalid
+    pub fn filter(&self, msg: &mut Msg) -> Vec<RecipientError> {
+        let channel = msg.channel();
+        let lists = match msg {
+            Msg::Email(email) => vec![&mut email.recipients, &mut email.cc, &mut email.bcc],
+            Msg::Sms(sms) => vec![&mut sms.recipients],
+            Msg::InApp(_) => return vec![],
+        };
+
+        let inner = self.inner.lock().unwrap();
+        let mut errors = vec![];
+        for recipients in lists {
+            recipients.retain(|recipient| match inner.entries.get(&key(recipient)) {
+                Some(entry) => {
+                    errors.push(RecipientError {
+                        recipient: recipient.clone(),
+                        reason: format!("suppressed: {}", entry.reason),
+                    });
+                    false
+                }
+                None => true,
+            });
+        }
+
+        if !errors.is_empty() {
+            metrics::counter!("crm_send_suppressed_total", "channel" => channel)
+                .increment(errors.len() as u64);
+        }
+        errors
+    }
+}
+
+/// most mail servers ignore the case of the local part, so a suppressed address is matched
+/// whatever its case
+fn key(recipient: &str) -> String {
+    recipient.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::EmailMessage;
+
+    #[test]
+    fn suppression_should_survive_restart() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("suppression.jsonl");
+        let config = SuppressionConfig {
+            path: Some(path.clone()),
+        };
+
+        let suppression = Suppression::load(&config)?;
+        assert!(suppression.add("tyr@acme.org", "hard bounce"));
+        assert!(!suppression.add("Tyr@acme.org", "complaint"));
+        drop(suppression);
+
+        let suppression = Suppression::load(&config)?;
+        let mut email = EmailMessage::fake();
+        email.recipients = vec!["TYR@acme.org".to_string(), "alice@acme.org".to_string()];
+        email.cc = vec!["tyr@acme.org".to_string()];
+        email.bcc = vec!["Tyr@acme.org".to_string(), "bob@acme.org".to_string()];
+        let mut msg = Msg::Email(email);
+        let errors = suppression.filter(&mut msg);
+        assert_eq!(msg.recipients(), ["alice@acme.org"]);
+        let suppressed: Vec<_> = errors.iter().map(|e| e.recipient.as_str()).collect();
+        assert_eq!(suppressed, ["TYR@acme.org", "tyr@acme.org", "Tyr@acme.org"]);
+        assert_eq!(errors[0].reason, "suppressed: hard bounce");
+        let Msg::Email(email) = msg else {
+            unreachable!()
+        };
+        assert!(email.cc.is_empty());
+        assert_eq!(email.bcc, ["bob@acme.org"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,539 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::{bail, Result};
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Redirect, Response as HttpResponse},
+    routing::get,
+    Router,
+};
+use hashlink::LruCache;
+use hmac::{Hmac, Mac};
+use prost_types::Timestamp;
+use serde::Deserialize;
+use sha2::Sha256;
+use tonic::{Response, Status};
+use tracing::{debug, warn};
+use url::form_urlencoded::byte_serialize;
+
+use crate::{
+    config::TrackingConfig,
+    pb::{CampaignStats, DeliveryStatus, EmailMessage, GetCampaignStatsRequest},
+    NotificationService, ServiceResult,
+};
+
+use super::to_ts;
+
+/// the secret of the sample config, which must not be used to sign real urls
+const PLACEHOLDER_SECRET: &str = "change-me";
+
+/// a transparent 1x1 gif
+const PIXEL: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+/// rewrites the links of html emails to the click redirect, adds the open pixel, and keeps
+/// the opens and clicks per campaign, and per message for the most recent ones. They're
+/// kept in memory only, and start over on restart
+pub struct Tracker {
+    config: TrackingConfig,
+    started_at: Timestamp,
+    inner: Mutex<TrackerInner>,
+}
+
+struct TrackerInner {
+    messages: LruCache<String, Engagement>,
+    campaigns: HashMap<String, CampaignStats>,
+}
+
+struct Engagement {
+    campaign: String,
+    opens: u64,
+    clicks: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenParams {
+    m: String,
+    s: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClickParams {
+    m: String,
+    u: String,
+    s: String,
+}
+
+impl Tracker {
+    pub fn new(config: TrackingConfig) -> Result<Self> {
+        if config.secret.is_empty() || config.secret == PLACEHOLDER_SECRET {
+            bail!("Tracking secret must be set to a random value");
+        }
+        let inner = TrackerInner {
+            messages: LruCache::new(config.capacity.max(1)),
+            campaigns: HashMap::new(),
+        };
+        Ok(Self {
+            config,
+            started_at: to_ts(),
+            inner: Mutex::new(inner),
+        })
+    }
+
+    /// rewrite the http(s) links of the html body and add the open pixel. Plain text emails
+    /// can't be tracked and are left as is
+    pub fn instrument(&self, email: &mut EmailMessage) {
+        if email.html_body.is_empty() {
+            return;
+        }
+
+        let message_id = email.message_id.clone();
+        let mut html = rewrite_links(&email.html_body, |url| self.click_url(&message_id, url));
+        let pixel = format!(
+            r#"<img src="{}" width="1" height="1" alt="" style="display:none">"#,
+            escape_attr(&self.open_url(&message_id))
+        );
+        match html.to_ascii_lowercase().rfind("</body>") {
+            Some(pos) => html.insert_str(pos, &pixel),
+            None => html.push_str(&pixel),
+        }
+        email.html_body = html;
+
+        self.inner.lock().unwrap().messages.insert(
+            message_id,
+            Engagement {
+                campaign: email.campaign.clone(),
+                opens: 0,
+                clicks: 0,
+            },
+        );
+    }
+
+    /// count the instrumented email in its campaign once it's delivered
+    pub fn sent(&self, email: &EmailMessage) {
+        if email.html_body.is_empty() || email.campaign.is_empty() {
+            return;
+        }
+        self.inner.lock().unwrap().campaign(&email.campaign).sent += 1;
+    }
+
+    pub fn stats(&self, campaign: &str) -> Option<CampaignStats> {
+        let stats = self
+            .inner
+            .lock()
+            .unwrap()
+            .campaigns
+            .get(campaign)
+            .cloned()?;
+        Some(CampaignStats {
+            since: Some(self.started_at.clone()),
+            ..stats
+        })
+    }
+
+    fn open_url(&self, message_id: &str) -> String {
+        format!(
+            "{}/o?m={}&s={}",
+            self.config.base_url.trim_end_matches('/'),
+            encode(message_id),
+            self.sign(&["o", message_id])
+        )
+    }
+
+    fn click_url(&self, message_id: &str, url: &str) -> String {
+        format!(
+            "{}/c?m={}&u={}&s={}",
+            self.config.base_url.trim_end_matches('/'),
+            encode(message_id),
+            encode(url),
+            self.sign(&["c", message_id, url])
+        )
+    }
+
+    /// a truncated HMAC-SHA256 is enough to make the urls unforgeable and keeps them short
+    fn sign(&self, parts: &[&str]) -> String {
+        // hmac accepts keys of any length
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.config.secret.as_bytes()).unwrap();
+        mac.update(parts.join("\n").as_bytes());
+        hex::encode(&mac.finalize().into_bytes()[..16])
+    }
+
+    fn verify(&self, parts: &[&str], signature: &str) -> bool {
+        match hex::decode(signature) {
+            Ok(signature) if signature.len() == 16 => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(self.config.secret.as_bytes()).unwrap();
+                mac.update(parts.join("\n").as_bytes());
+                mac.verify_truncated_left(&signature).is_ok()
+            }
+            _ => false,
+        }
+    }
+
+    /// count the event; returns whether it's the first of its kind for the message
+    fn track(&self, message_id: &str, click: bool) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(engagement) = inner.messages.get_mut(message_id) else {
+            // sent before a restart, or dropped for the more recent ones, it's not counted
+            return false;
+        };
+        let count = if click {
+            &mut engagement.clicks
+        } else {
+            &mut engagement.opens
+        };
+        *count += 1;
+        let first = *count == 1;
+        let campaign = engagement.campaign.clone();
+        if campaign.is_empty() {
+            return first;
+        }
+
+        let stats = inner.campaign(&campaign);
+        match (click, first) {
+            (true, true) => {
+                stats.clicks += 1;
+                stats.clicked += 1;
+            }
+            (true, false) => stats.clicks += 1,
+            (false, true) => {
+                stats.opens += 1;
+                stats.opened += 1;
+            }
+            (false, false) => stats.opens += 1,
+        }
+        first
+    }
+}
+
+impl TrackerInner {
+    fn campaign(&mut self, campaign: &str) -> &mut CampaignStats {
+        self.campaigns
+            .entry(campaign.to_string())
+            .or_insert_with(|| CampaignStats {
+                campaign: campaign.to_string(),
+                ..Default::default()
+            })
+    }
+}
+
+impl NotificationService {
+    pub async fn get_campaign_stats(
+        &self,
+        req: GetCampaignStatsRequest,
+    ) -> ServiceResult<CampaignStats> {
+        let Some(tracker) = self.tracker.as_ref() else {
+            return Err(Status::failed_precondition("Tracking is disabled"));
+        };
+        match tracker.stats(&req.campaign) {
+            Some(stats) => Ok(Response::new(stats)),
+            None => Err(Status::not_found(format!(
+                "Campaign {} not found",
+                req.campaign
+            ))),
+        }
+    }
+
+    /// http routes of the tracking pixel and the link redirects
+    pub fn tracking_router(&self) -> Router {
+        Router::new()
+            .route("/o", get(open))
+            .route("/c", get(click))
+            .with_state(self.clone())
+    }
+}
+
+async fn open(
+    State(svc): State<NotificationService>,
+    Query(params): Query<OpenParams>,
+) -> HttpResponse {
+    let pixel = (
+        [
+            (header::CONTENT_TYPE, "image/gif"),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        PIXEL,
+    );
+    let Some(tracker) = svc.tracker.as_ref() else {
+        return pixel.into_response();
+    };
+    // never break the rendering of the email, even for a forged url
+    if tracker.verify(&["o", &params.m], &params.s) {
+        debug!("Message {} opened", params.m);
+        metrics::counter!("crm_send_tracking_events_total", "event" => "open").increment(1);
+        if tracker.track(&params.m, false) {
+            svc.status
+                .record(&params.m, DeliveryStatus::Opened, "")
+                .await;
+        }
+    } else {
+        warn!("Invalid open tracking signature for message {}", params.m);
+    }
+    pixel.into_response()
+}
+
+async fn click(
+    State(svc): State<NotificationService>,
+    Query(params): Query<ClickParams>,
+) -> HttpResponse {
+    let tracker = match svc.tracker.as_ref() {
+        Some(tracker) if tracker.verify(&["c", &params.m, &params.u], &params.s) => tracker,
+        _ => {
+            // an invalid signature would make us an open redirect
+            warn!("Invalid click tracking signature for message {}", params.m);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    debug!("Message {} clicked: {}", params.m, params.u);
+    metrics::counter!("crm_send_tracking_events_total", "event" => "click").increment(1);
+    // the repeated clicks are only counted in the campaign stats
+    if tracker.track(&params.m, true) {
+        svc.status
+            .record(&params.m, DeliveryStatus::Clicked, params.u.clone())
+            .await;
+    }
+    Redirect::to(&params.u).into_response()
+}
+
+/// replace the http(s) urls in the href attributes of the html
+fn rewrite_links(html: &str, rewrite: impl Fn(&str) -> String) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len() * 2);
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find("href=") {
+        let attr = pos + found + "href=".len();
+        let Some(quote) = html[attr..]
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+        else {
+            out.push_str(&html[pos..attr]);
+            pos = attr;
+            continue;
+        };
+        let start = attr + 1;
+        let Some(len) = html[start..].find(quote) else {
+            break;
+        };
+        let end = start + len;
+
+        out.push_str(&html[pos..start]);
+        let url = html[start..end].replace("&amp;", "&");
+        let lower_url = url.to_ascii_lowercase();
+        if lower_url.starts_with("http://") || lower_url.starts_with("https://") {
+            out.push_str(&escape_attr(&rewrite(&url)));
+        } else {
+            // mailto:, tel:, anchors and the like
+            out.push_str(&html[start..end]);
+        }
+        pos = end;
+    }
+    out.push_str(&html[pos..]);
+    out
+}
+
+fn encode(s: &str) -> String {
+    byte_serialize(s.as_bytes()).collect()
+}
+
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abi::{Sink, StatusStore},
+        config::{EmailFormat, SinkConfig},
+        pb::send_request::Msg,
+        AppConfig,
+    };
+    use anyhow::Result;
+
+    fn config() -> TrackingConfig {
+        TrackingConfig {
+            port: 0,
+            base_url: "http://t.acme.org/".to_string(),
+            secret: "secret".to_string(),
+            capacity: 10,
+        }
+    }
+
+    #[test]
+    fn links_should_be_rewritten() {
+        let tracker = Tracker::new(config()).unwrap();
+        let mut email = EmailMessage::fake();
+        email.message_id = "1".to_string();
+        email.html_body = r##"<html><body><a href="https://acme.org/a?x=1&amp;y=2">a</a>
+            <A HREF='mailto:tyr@acme.org'>mail</A><a href="#top">top</a></body></html>"##
+            .to_string();
+        tracker.instrument(&mut email);
+
+        let url = "https://acme.org/a?x=1&y=2";
+        let expected = format!(
+            r#"<a href="http://t.acme.org/c?m=1&amp;u={}&amp;s={}">a</a>"#,
+            encode(url),
+            tracker.sign(&["c", "1", url])
+        );
+        assert!(email.html_body.contains(&expected));
+        assert!(email.html_body.contains("'mailto:tyr@acme.org'"));
+        assert!(email.html_body.contains(r##"href="#top""##));
+        let pixel = format!(
+            r#"<img src="http://t.acme.org/o?m=1&amp;s={}""#,
+            tracker.sign(&["o", "1"])
+        );
+        assert!(email.html_body.contains(&pixel));
+        assert!(email
+            .html_body
+            .ends_with(r#"style="display:none"></body></html>"#));
+    }
+
+    #[test]
+    fn signature_should_verify() {
+        let tracker = Tracker::new(config()).unwrap();
+        let signature = tracker.sign(&["c", "1", "https://acme.org"]);
+        assert!(tracker.verify(&["c", "1", "https://acme.org"], &signature));
+        assert!(!tracker.verify(&["c", "1", "https://evil.org"], &signature));
+        assert!(!tracker.verify(&["c", "1", "https://acme.org"], &signature[..8]));
+
+        for secret in ["", PLACEHOLDER_SECRET] {
+            let config = TrackingConfig {
+                secret: secret.to_string(),
+                ..config()
+            };
+            assert!(Tracker::new(config).is_err());
+        }
+    }
+
+    #[test]
+    fn tracker_should_keep_recent_messages() {
+        let tracker = Tracker::new(TrackingConfig {
+            capacity: 1,
+            ..config()
+        })
+        .unwrap();
+        let emails: Vec<_> = (0..2)
+            .map(|_| {
+                let mut email = EmailMessage::fake();
+                email.html_body = "<p>hi</p>".to_string();
+                email.campaign = "recall".to_string();
+                tracker.instrument(&mut email);
+                tracker.sent(&email);
+                email
+            })
+            .collect();
+
+        assert!(!tracker.track(&emails[0].message_id, false));
+        assert!(tracker.track(&emails[1].message_id, false));
+        let stats = tracker.stats("recall").unwrap();
+        assert_eq!((stats.sent, stats.opens), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn campaign_should_only_count_delivered_emails() -> Result<()> {
+        let tracker = Tracker::new(config())?;
+        let dir = tempfile::tempdir()?;
+        let sink = Sink::new(&SinkConfig::File {
+            dir: dir.path().to_path_buf(),
+            email_format: EmailFormat::Eml,
+        })?;
+        let status = StatusStore::default();
+        for message_id in ["delivered", "../failed"] {
+            let mut email = EmailMessage::fake();
+            email.message_id = message_id.to_string();
+            email.html_body = "<p>hi</p>".to_string();
+            email.campaign = "recall".to_string();
+            tracker.instrument(&mut email);
+            sink.send(Msg::Email(email), &status, Some(&tracker)).await;
+        }
+
+        assert_eq!(tracker.stats("recall").unwrap().sent, 1);
+        let status_of = |id: &str| status.get(id).unwrap().status;
+        assert_eq!(status_of("delivered"), DeliveryStatus::Delivered as i32);
+        assert_eq!(status_of("../failed"), DeliveryStatus::Failed as i32);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn opens_and_clicks_should_be_tracked() -> Result<()> {
+        let mut config = AppConfig::load_for_test()?;
+        config.tracking = Some(self::config());
+        let service = NotificationService::new(config)?;
+        let tracker = service.tracker.as_ref().unwrap();
+
+        let mut emails: Vec<_> = (0..2)
+            .map(|_| {
+                let mut email = EmailMessage::fake();
+                email.html_body = r#"<a href="https://acme.org">acme</a>"#.to_string();
+                email.campaign = "recall".to_string();
+                tracker.instrument(&mut email);
+                tracker.sent(&email);
+                email
+            })
+            .collect();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let server =
+            axum::Server::from_tcp(listener)?.serve(service.tracking_router().into_make_service());
+        tokio::spawn(server);
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        let local = |html: &str, prefix: &str| {
+            let start = html.find(prefix).unwrap() + prefix.len();
+            let end = start + html[start..].find('"').unwrap();
+            html[start..end]
+                .replace("&amp;", "&")
+                .replace("http://t.acme.org", &format!("http://{}", addr))
+        };
+
+        let email = emails.remove(0);
+        let open = local(&email.html_body, r#"<img src=""#);
+        for _ in 0..2 {
+            let res = client.get(&open).send().await?;
+            assert_eq!(res.headers()["content-type"], "image/gif");
+        }
+        let click = local(&email.html_body, r#"<a href=""#);
+        for _ in 0..2 {
+            let res = client.get(&click).send().await?;
+            assert_eq!(res.status(), reqwest::StatusCode::SEE_OTHER);
+            assert_eq!(res.headers()["location"], "https://acme.org");
+        }
+        let res = client
+            .get(click.replace("acme.org", "evil.org"))
+            .send()
+            .await?;
+        assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        let stats = service
+            .get_campaign_stats(GetCampaignStatsRequest {
+                campaign: "recall".to_string(),
+            })
+            .await?
+            .into_inner();
+        assert_eq!(stats.sent, 2);
+        assert_eq!(stats.opened, 1);
+        assert_eq!(stats.opens, 2);
+        assert_eq!(stats.clicked, 1);
+        assert_eq!(stats.clicks, 2);
+        assert_eq!(stats.since, Some(tracker.started_at.clone()));
+
+        let status = service.status.get(&email.message_id).unwrap();
+        let statuses: Vec<_> = status.events.iter().map(|e| e.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                DeliveryStatus::Opened as i32,
+                DeliveryStatus::Clicked as i32
+            ]
+        );
+        Ok(())
+    }
+}
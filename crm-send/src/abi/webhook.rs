@@ -0,0 +1,366 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::{
+    sync::{mpsc, Semaphore},
+    time::sleep,
+};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::{
+    config::{RetryConfig, WebhookConfig, WebhookEndpoint},
+    pb::{DeliveryStatus, StatusEvent},
+    WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+
+use super::ts_to_utc;
+
+/// posts the delivery events to the configured endpoints, signed with the endpoint secret
+pub struct Webhooks {
+    client: reqwest::Client,
+    endpoints: Vec<Arc<WebhookEndpoint>>,
+    retry: RetryConfig,
+    queue_size: usize,
+    concurrency: usize,
+}
+
+/// the json body of a webhook request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// unique per event, so receivers could ignore the retried ones they already got
+    pub id: String,
+    /// accepted, delivered, failed, bounced or complained
+    pub event: String,
+    pub message_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub detail: String,
+}
+
+impl Webhooks {
+    pub fn new(config: &WebhookConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.retry.timeout_ms))
+            .build()?;
+        Ok(Self {
+            client,
+            endpoints: config.endpoints.iter().cloned().map(Arc::new).collect(),
+            retry: config.retry.clone(),
+            queue_size: config.queue_size.max(1),
+            concurrency: config.concurrency.max(1),
+        })
+    }
+
+    /// start posting the status changes sent to the returned outbox, which is none if there is
+    /// no endpoint. The outbox is bounded and so is the number of deliveries in progress, a
+    /// slow endpoint holds the status changes back instead of dropping them
+    pub fn start(self) -> Option<mpsc::Sender<StatusEvent>> {
+        if self.endpoints.is_empty() {
+            return None;
+        }
+
+        let (tx, mut rx) = mpsc::channel(self.queue_size);
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let webhooks = Arc::new(self);
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let Some(event) = WebhookEvent::from_status(event) else {
+                    continue;
+                };
+                for endpoint in webhooks.endpoints.iter() {
+                    if !endpoint.wants(&event.event) {
+                        continue;
+                    }
+                    // the semaphore is never closed
+                    let permit = permits.clone().acquire_owned().await.unwrap();
+                    let webhooks = webhooks.clone();
+                    let endpoint = endpoint.clone();
+                    let event = event.clone();
+                    tokio::spawn(async move {
+                        webhooks.deliver(endpoint, event).await;
+                        drop(permit);
+                    });
+                }
+            }
+        });
+        Some(tx)
+    }
+
+    /// post the event to the endpoint, retrying with exponential backoff
+    async fn deliver(self: Arc<Self>, endpoint: Arc<WebhookEndpoint>, event: WebhookEvent) {
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize webhook event {:?}: {:?}", event, e);
+                return;
+            }
+        };
+
+        let mut backoff = Duration::from_millis(self.retry.backoff_ms);
+        let max_attempts = self.retry.max_attempts.max(1);
+        for attempt in 1..=max_attempts {
+            match self.post(&endpoint, &body).await {
+                Ok(()) => {
+                    debug!("Webhook event {} posted to {}", event.id, endpoint.url);
+                    metrics::counter!("crm_send_webhook_deliveries_total", "result" => "ok")
+                        .increment(1);
+                    return;
+                }
+                Err(e) if attempt < max_attempts => {
+                    debug!(
+                        "Failed to post webhook event {} to {} (attempt {}): {:?}",
+                        event.id, endpoint.url, attempt, e
+                    );
+                    metrics::counter!("crm_send_webhook_retries_total").increment(1);
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => {
+                    warn!(
+                        "Give up posting webhook event {} to {} after {} attempts: {:?}",
+                        event.id, endpoint.url, attempt, e
+                    );
+                    metrics::counter!("crm_send_webhook_deliveries_total", "result" => "failed")
+                        .increment(1);
+                }
+            }
+        }
+    }
+
+    async fn post(&self, endpoint: &WebhookEndpoint, body: &[u8]) -> Result<()> {
+        let timestamp = Utc::now().timestamp();
+        let signature = sign(&endpoint.secret, timestamp, body);
+        let res = self
+            .client
+            .post(&endpoint.url)
+            .header("content-type", "application/json")
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp)
+            .header(WEBHOOK_SIGNATURE_HEADER, signature)
+            .body(body.to_vec())
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            bail!("Unexpected status: {}", res.status());
+        }
+        Ok(())
+    }
+}
+
+impl WebhookEndpoint {
+    fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event)
+    }
+}
+
+impl WebhookEvent {
+    /// only the progress of a delivery is posted, not the engagement events. Delivered is the
+    /// one recorded once the message is handed over to the provider
+    fn from_status(event: StatusEvent) -> Option<Self> {
+        let name = match DeliveryStatus::try_from(event.status).ok()? {
+            DeliveryStatus::Accepted => "accepted",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed | DeliveryStatus::Expired => "failed",
+            DeliveryStatus::Bounced => "bounced",
+            DeliveryStatus::Complained => "complained",
+            _ => return None,
+        };
+        let timestamp = event
+            .timestamp
+            .and_then(|ts| ts_to_utc(ts).ok())
+            .unwrap_or_else(Utc::now);
+        Some(Self {
+            id: Uuid::new_v4().to_string(),
+            event: name.to_string(),
+            message_id: event.message_id,
+            timestamp,
+            detail: event.detail,
+        })
+    }
+}
+
+/// hex encoded HMAC-SHA256 of `{timestamp}.{body}`, prefixed with `sha256=`. The timestamp is
+/// signed along with the body, so receivers could reject replayed requests
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mac = new_mac(secret, timestamp, body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// check the signature of a webhook request in constant time
+pub fn verify_signature(secret: &str, timestamp: i64, body: &[u8], signature: &str) -> bool {
+    let Some(signature) = signature
+        .strip_prefix("sha256=")
+        .and_then(|s| hex::decode(s).ok())
+    else {
+        return false;
+    };
+    new_mac(secret, timestamp, body)
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+fn new_mac(secret: &str, timestamp: i64, body: &[u8]) -> Hmac<Sha256> {
+    // hmac accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abi::StatusStore, config::StatusConfig};
+    use axum::{body::Bytes, extract::State, http::HeaderMap, http::StatusCode, routing::post};
+    use std::sync::Mutex;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn signature_should_verify() {
+        let signature = sign("secret", 42, b"{}");
+        assert!(verify_signature("secret", 42, b"{}", &signature));
+        assert!(!verify_signature("other", 42, b"{}", &signature));
+        assert!(!verify_signature("secret", 43, b"{}", &signature));
+        assert!(!verify_signature("secret", 42, b"{ }", &signature));
+        assert!(!verify_signature("secret", 42, b"{}", "sha256=zz"));
+    }
+
+    #[tokio::test]
+    async fn webhooks_should_retry_until_delivered() -> Result<()> {
+        // the receiver fails the first request, then forwards the verified events
+        let (tx, mut rx) = mpsc::channel(16);
+        let failures = Arc::new(Mutex::new(1));
+        let url = start_receiver("secret", tx, failures).await;
+
+        let config = webhook_config(url, vec!["delivered".to_string()]);
+        let status =
+            StatusStore::with_outbox(&StatusConfig::default(), Webhooks::new(&config)?.start());
+
+        status.record("1", DeliveryStatus::Accepted, "").await;
+        status.record("1", DeliveryStatus::Delivered, "").await;
+
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await?
+            .unwrap();
+        assert_eq!(event.event, "delivered");
+        assert_eq!(event.message_id, "1");
+        // the accepted event is filtered out by the endpoint
+        assert!(tokio::time::timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn webhooks_should_not_drop_events_when_behind() -> Result<()> {
+        let (tx, mut rx) = mpsc::channel(1);
+        let url = start_receiver("secret", tx, Arc::new(Mutex::new(0))).await;
+
+        let mut config = webhook_config(url, vec![]);
+        config.queue_size = 1;
+        config.concurrency = 1;
+        let status =
+            StatusStore::with_outbox(&StatusConfig::default(), Webhooks::new(&config)?.start());
+
+        let recorder = tokio::spawn(async move {
+            for i in 0..50 {
+                status
+                    .record(&i.to_string(), DeliveryStatus::Delivered, "")
+                    .await;
+            }
+        });
+        let mut ids = vec![];
+        while ids.len() < 50 {
+            let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await?
+                .unwrap();
+            ids.push(event.message_id.parse::<u32>()?);
+        }
+        recorder.await?;
+        ids.sort();
+        assert_eq!(ids, (0..50).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn webhooks_should_support_https() -> Result<()> {
+        let webhooks = Webhooks::new(&WebhookConfig::default())?;
+        // nothing listens on the port, so the request gets as far as connecting, instead of
+        // failing on the scheme when TLS is not built in
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        let err = webhooks
+            .client
+            .post(format!("https://{}/events", addr))
+            .send()
+            .await
+            .unwrap_err();
+        let refused = std::iter::successors(Some(&err as &dyn std::error::Error), |e| e.source())
+            .filter_map(|e| e.downcast_ref::<std::io::Error>())
+            .any(|e| e.kind() == std::io::ErrorKind::ConnectionRefused);
+        assert!(refused, "{:?}", err);
+        Ok(())
+    }
+
+    fn webhook_config(url: String, events: Vec<String>) -> WebhookConfig {
+        WebhookConfig {
+            endpoints: vec![WebhookEndpoint {
+                url,
+                secret: "secret".to_string(),
+                events,
+            }],
+            retry: RetryConfig {
+                max_attempts: 3,
+                backoff_ms: 10,
+                timeout_ms: 1000,
+            },
+            ..Default::default()
+        }
+    }
+
+    type ReceiverState = (String, mpsc::Sender<WebhookEvent>, Arc<Mutex<u32>>);
+
+    async fn start_receiver(
+        secret: &str,
+        tx: mpsc::Sender<WebhookEvent>,
+        failures: Arc<Mutex<u32>>,
+    ) -> String {
+        async fn receive(
+            State((secret, tx, failures)): State<ReceiverState>,
+            headers: HeaderMap,
+            body: Bytes,
+        ) -> StatusCode {
+            {
+                let mut failures = failures.lock().unwrap();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+            }
+            let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+            let timestamp = header(WEBHOOK_TIMESTAMP_HEADER).and_then(|v| v.parse().ok());
+            let signature = header(WEBHOOK_SIGNATURE_HEADER).unwrap_or_default();
+            match timestamp {
+                Some(ts) if verify_signature(&secret, ts, &body, signature) => {
+                    tx.send(serde_json::from_slice(&body).unwrap())
+                        .await
+                        .unwrap();
+                    StatusCode::OK
+                }
+                _ => StatusCode::UNAUTHORIZED,
+            }
+        }
+
+        let app = axum::Router::new()
+            .route("/events", post(receive))
+            .with_state((secret.to_string(), tx, failures));
+        let server =
+            axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+        let url = format!("http://{}/events", server.local_addr());
+        tokio::spawn(server);
+        url
+    }
+}
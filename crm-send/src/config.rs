@@ -1,4 +1,4 @@
-use std::{fs::File, io::Read};
+use std::{collections::HashMap, fs::File, io::Read, path::PathBuf};
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
@@ -7,11 +7,33 @@ use serde::{Deserialize, Serialize};
 pub struct AppConfig {
     pub server: ServerConfig,
     pub auth: AuthConfig,
+    #[serde(default)]
+    pub dedupe: DedupeConfig,
+    #[serde(default)]
+    pub status: StatusConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub dispatch: DispatchConfig,
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    #[serde(default)]
+    pub suppression: SuppressionConfig,
+    #[serde(default)]
+    pub sink: SinkConfig,
+    /// open and click tracking of html emails, disabled if not set
+    #[serde(default)]
+    pub tracking: Option<TrackingConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub port: u16,
+    /// port of the prometheus metrics endpoint, disabled if not set
+    pub metrics_port: Option<u16>,
+    /// how long (in seconds) to drain the in-flight requests and the queues on shutdown
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,6 +41,223 @@ pub struct AuthConfig {
     pub pk: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DedupeConfig {
+    /// how long (in seconds) a message_id is remembered
+    pub ttl: u64,
+    /// journal file to keep the dedupe state across restarts, in memory only if not set
+    pub path: Option<PathBuf>,
+}
+
+impl Default for DedupeConfig {
+    fn default() -> Self {
+        Self {
+            ttl: 24 * 60 * 60,
+            path: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatusConfig {
+    /// how long (in seconds) the status of a message is kept after its last change
+    pub ttl: u64,
+    /// max messages whose status is kept, the least recently changed are dropped first
+    pub capacity: usize,
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            ttl: 7 * 24 * 60 * 60,
+            capacity: 1_000_000,
+        }
+    }
+}
+
+/// where the messages are delivered to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// log the messages only
+    #[default]
+    Log,
+    /// write the messages under the dir: emails as .eml files or a single mbox,
+    /// sms and in-app messages as jsonl
+    File {
+        dir: PathBuf,
+        #[serde(default)]
+        email_format: EmailFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailFormat {
+    /// one email/<message_id>.eml file per email
+    #[default]
+    Eml,
+    /// all emails appended to email.mbox
+    Mbox,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingConfig {
+    /// port of the http listener serving the tracking pixel and the link redirects
+    pub port: u16,
+    /// url of the listener as reached by the recipients, e.g. https://t.example.com
+    pub base_url: String,
+    /// key to sign the tracking urls, so they can't be forged into an open redirect
+    pub secret: String,
+    /// max messages whose opens and clicks are tracked, the least recently sent or engaged
+    /// are dropped first
+    #[serde(default = "default_tracking_capacity")]
+    pub capacity: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SuppressionConfig {
+    /// journal file to keep the suppression list across restarts, in memory only if not set
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// limits per channel: email, sms, in_app
+    #[serde(default)]
+    pub channels: HashMap<String, BucketConfig>,
+    /// limits per recipient domain, e.g. gmail.com
+    #[serde(default)]
+    pub domains: HashMap<String, BucketConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketConfig {
+    /// tokens refilled per second
+    pub rate: f64,
+    /// max tokens the bucket could hold
+    pub burst: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DispatchConfig {
+    /// max requests of a send stream processed at the same time
+    pub concurrency: usize,
+    /// max requests of a batch send
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+    /// workers per channel: email, sms, in_app; 1 if not set
+    #[serde(default)]
+    pub workers: HashMap<String, usize>,
+    /// share of the workers each priority lane gets when all lanes are busy
+    #[serde(default)]
+    pub weights: LaneWeights,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaneWeights {
+    pub high: u32,
+    pub normal: u32,
+    pub low: u32,
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 16,
+            max_batch_size: default_max_batch_size(),
+            workers: HashMap::new(),
+            weights: LaneWeights::default(),
+        }
+    }
+}
+
+fn default_max_batch_size() -> usize {
+    1000
+}
+
+fn default_shutdown_timeout() -> u64 {
+    30
+}
+
+fn default_webhook_queue_size() -> usize {
+    1024
+}
+
+fn default_webhook_concurrency() -> usize {
+    16
+}
+
+fn default_tracking_capacity() -> usize {
+    1_000_000
+}
+
+impl Default for LaneWeights {
+    fn default() -> Self {
+        Self {
+            high: 8,
+            normal: 4,
+            low: 1,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// endpoints to POST the delivery events to
+    #[serde(default)]
+    pub endpoints: Vec<WebhookEndpoint>,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// status changes waiting to be posted; once it's full, new changes wait for room
+    #[serde(default = "default_webhook_queue_size")]
+    pub queue_size: usize,
+    /// max number of requests in progress across the endpoints
+    #[serde(default = "default_webhook_concurrency")]
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// key of the HMAC-SHA256 signature of the events
+    pub secret: String,
+    /// events to send: accepted, delivered, failed, bounced, complained; all if empty
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// attempts before an event is given up, including the first one
+    pub max_attempts: u32,
+    /// delay (in milliseconds) before the first retry, doubled for each retry after
+    pub backoff_ms: u64,
+    /// timeout (in milliseconds) of each attempt
+    pub timeout_ms: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: vec![],
+            retry: RetryConfig::default(),
+            queue_size: default_webhook_queue_size(),
+            concurrency: default_webhook_concurrency(),
+        }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            backoff_ms: 500,
+            timeout_ms: 5000,
+        }
+    }
+}
+
 impl AppConfig {
     pub fn load() -> Result<Self> {
         if let Ok(reader) = File::open("send.yml") {
@@ -2,11 +2,16 @@ mod abi;
 mod config;
 pub mod pb;
 
-pub use config::AppConfig;
+pub use abi::{verify_signature, DrainReport, WebhookEvent};
+use abi::{Dedupe, Dispatcher, StatusStore, Suppression, Tracker};
+pub use config::{AppConfig, EmailFormat, SinkConfig};
 use futures::Stream;
-use pb::{notification_server::Notification, send_request::Msg, SendRequest, SendResponse};
+use pb::{
+    notification_server::Notification, CampaignStats, GetCampaignStatsRequest, GetStatusRequest,
+    GetStatusResponse, ReportBounceRequest, ReportBounceResponse, SendBatchRequest,
+    SendBatchResponse, SendRequest, SendResponse, StatusEvent, WatchStatusRequest,
+};
 use std::{pin::Pin, sync::Arc};
-use tokio::sync::mpsc;
 use tonic::{async_trait, Request, Response, Status, Streaming};
 
 #[derive(Clone)]
@@ -17,21 +22,96 @@ pub struct NotificationService {
 #[allow(unused)]
 pub struct NotificationServiceInner {
     config: AppConfig,
-    sender: mpsc::Sender<Msg>,
+    sender: Dispatcher,
+    dedupe: Dedupe,
+    status: StatusStore,
+    suppression: Suppression,
+    tracker: Option<Arc<Tracker>>,
 }
 
+/// request metadata to get the acks of a send stream in request order
+pub const ORDERED_ACKS_HEADER: &str = "x-ordered-acks";
+/// webhook request header with the unix timestamp the request is signed at
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "x-crm-timestamp";
+/// webhook request header with the HMAC-SHA256 signature, see [`verify_signature`]
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-crm-signature";
+
 type ServiceResult<T> = Result<Response<T>, Status>;
 type ResponseStream = Pin<Box<dyn Stream<Item = Result<SendResponse, Status>> + Send>>;
+type StatusStream = Pin<Box<dyn Stream<Item = Result<StatusEvent, Status>> + Send>>;
 
 #[async_trait]
 impl Notification for NotificationService {
     type SendStream = ResponseStream;
+    type WatchStatusStream = StatusStream;
 
     async fn send(
         &self,
         request: Request<Streaming<SendRequest>>,
     ) -> Result<Response<Self::SendStream>, Status> {
+        let ordered = request
+            .metadata()
+            .get(ORDERED_ACKS_HEADER)
+            .is_some_and(|v| v == "true");
         let stream = request.into_inner();
-        self.send(stream).await
+        self.send(stream, ordered).await
+    }
+
+    async fn send_batch(
+        &self,
+        request: Request<SendBatchRequest>,
+    ) -> ServiceResult<SendBatchResponse> {
+        let req = request.into_inner();
+        self.send_batch(req).await
+    }
+
+    async fn get_status(
+        &self,
+        request: Request<GetStatusRequest>,
+    ) -> ServiceResult<GetStatusResponse> {
+        let req = request.into_inner();
+        self.get_status(req).await
+    }
+
+    async fn watch_status(
+        &self,
+        request: Request<WatchStatusRequest>,
+    ) -> ServiceResult<Self::WatchStatusStream> {
+        let req = request.into_inner();
+        self.watch_status(req).await
+    }
+
+    async fn report_bounce(
+        &self,
+        request: Request<ReportBounceRequest>,
+    ) -> ServiceResult<ReportBounceResponse> {
+        let req = request.into_inner();
+        self.report_bounce(req).await
+    }
+
+    async fn get_campaign_stats(
+        &self,
+        request: Request<GetCampaignStatsRequest>,
+    ) -> ServiceResult<CampaignStats> {
+        let req = request.into_inner();
+        self.get_campaign_stats(req).await
+    }
+}
+
+#[cfg(feature = "test_utils")]
+mod test_utils {
+    use anyhow::Result;
+
+    use crate::AppConfig;
+
+    impl AppConfig {
+        /// the config of send.yml, with the dedupe journal and the suppression list kept in
+        /// memory so tests don't share them
+        pub fn load_for_test() -> Result<Self> {
+            let mut config = Self::load()?;
+            config.dedupe.path = None;
+            config.suppression.path = None;
+            Ok(config)
+        }
     }
 }
@@ -1,7 +1,10 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use crm_send::{AppConfig, NotificationService};
+use metrics_exporter_prometheus::PrometheusBuilder;
 use tonic::transport::Server;
-use tracing::{info, level_filters::LevelFilter};
+use tracing::{info, level_filters::LevelFilter, warn};
 use tracing_subscriber::{fmt::Layer, layer::SubscriberExt, util::SubscriberInitExt, Layer as _};
 
 #[tokio::main]
@@ -12,9 +15,54 @@ async fn main() -> Result<()> {
     let config = AppConfig::load().expect("Failed to load config");
     let addr = config.server.port;
     let addr = format!("[::1]:{}", addr).parse().unwrap();
+    let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout);
     info!("Notification service listening on {}", addr);
 
-    let svc = NotificationService::new(config).into_server();
-    Server::builder().add_service(svc).serve(addr).await?;
+    if let Some(port) = config.server.metrics_port {
+        let metrics_addr: std::net::SocketAddr = format!("[::1]:{}", port).parse()?;
+        PrometheusBuilder::new()
+            .with_http_listener(metrics_addr)
+            .install()?;
+        info!("Metrics exposed on http://{}/metrics", metrics_addr);
+    }
+
+    let tracking_port = config.tracking.as_ref().map(|t| t.port);
+    let svc = NotificationService::new(config)?;
+    if let Some(port) = tracking_port {
+        let tracking_addr: std::net::SocketAddr = format!("[::1]:{}", port).parse()?;
+        let server =
+            axum::Server::bind(&tracking_addr).serve(svc.tracking_router().into_make_service());
+        tokio::spawn(server);
+        info!("Tracking listening on http://{}", tracking_addr);
+    }
+
+    // on shutdown, stop accepting requests, let the in-flight ones finish, then drain the queues
+    let server = |stop| {
+        Server::builder()
+            .add_service(svc.clone().into_server())
+            .serve_with_shutdown(addr, stop)
+    };
+    let Some(deadline) = crm_shutdown::serve(server, shutdown_timeout).await? else {
+        return Ok(());
+    };
+
+    let report = svc.drain(deadline).await;
+    if report.is_empty() {
+        info!("All queued messages sent");
+    }
+    if !report.queued.is_empty() {
+        warn!(
+            "{} queued messages not sent before the deadline: {:?}",
+            report.queued.len(),
+            report.queued
+        );
+    }
+    if !report.scheduled.is_empty() {
+        warn!(
+            "{} messages scheduled for later are lost, they must be sent again: {:?}",
+            report.scheduled.len(),
+            report.scheduled
+        );
+    }
     Ok(())
 }
@@ -15,11 +15,51 @@ pub struct EmailMessage {
     /// recipients of the email
     #[prost(string, repeated, tag = "4")]
     pub recipients: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
-    /// body of the email
+    /// plain text body of the email
     #[prost(string, tag = "5")]
     pub body: ::prost::alloc::string::String,
+    /// html body of the email, sent along with the plain text body if set
+    #[prost(string, tag = "6")]
+    pub html_body: ::prost::alloc::string::String,
+    /// carbon copy recipients of the email
+    #[prost(string, repeated, tag = "7")]
+    pub cc: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// blind carbon copy recipients of the email
+    #[prost(string, repeated, tag = "8")]
+    pub bcc: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// address replies should go to, the sender if not set
+    #[prost(string, tag = "9")]
+    pub reply_to: ::prost::alloc::string::String,
+    /// custom headers of the email, e.g. List-Unsubscribe
+    #[prost(map = "string, string", tag = "10")]
+    pub headers:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    /// files attached to the email, or images inlined in the html body
+    #[prost(message, repeated, tag = "11")]
+    pub attachments: ::prost::alloc::vec::Vec<Attachment>,
+    /// campaign the email belongs to, for the open and click aggregates
+    #[prost(string, tag = "12")]
+    pub campaign: ::prost::alloc::string::String,
+}
+/// file attached to an email
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Attachment {
+    /// name of the file
+    #[prost(string, tag = "1")]
+    pub filename: ::prost::alloc::string::String,
+    /// mime type of the file, e.g. image/png
+    #[prost(string, tag = "2")]
+    pub content_type: ::prost::alloc::string::String,
+    /// content of the file
+    #[prost(bytes = "vec", tag = "3")]
+    pub content: ::prost::alloc::vec::Vec<u8>,
+    /// set to inline the file in the html body, referenced as cid:<content_id>
+    #[prost(string, tag = "4")]
+    pub content_id: ::prost::alloc::string::String,
 }
 /// sms message to be sent
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SmsMessage {
@@ -37,6 +77,7 @@ pub struct SmsMessage {
     pub body: ::prost::alloc::string::String,
 }
 /// in-app message to be sent
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct InAppMessage {
@@ -57,6 +98,16 @@ pub struct InAppMessage {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendRequest {
+    /// hold the message until this time, send immediately if not set. Scheduled messages are
+    /// kept in memory only, those not sent yet are lost when the service restarts
+    #[prost(message, optional, tag = "5")]
+    pub send_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// drop the message if it's still undelivered at this time, never expires if not set
+    #[prost(message, optional, tag = "6")]
+    pub expires_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// lane of the message in the dispatcher, normal if not set
+    #[prost(enumeration = "Priority", tag = "7")]
+    pub priority: i32,
     /// one of the message types to send
     #[prost(oneof = "send_request::Msg", tags = "2, 3, 4")]
     pub msg: ::core::option::Option<send_request::Msg>,
@@ -64,6 +115,7 @@ pub struct SendRequest {
 /// Nested message and enum types in `SendRequest`.
 pub mod send_request {
     /// one of the message types to send
+    #[allow(clippy::large_enum_variant)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Msg {
@@ -85,6 +137,320 @@ pub struct SendResponse {
     /// timestamp of when the message was sent
     #[prost(message, optional, tag = "2")]
     pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// ACCEPTED if the message is queued, FAILED if it has no valid recipient
+    #[prost(enumeration = "DeliveryStatus", tag = "3")]
+    pub status: i32,
+    /// recipients dropped from the message, and why
+    #[prost(message, repeated, tag = "4")]
+    pub invalid_recipients: ::prost::alloc::vec::Vec<RecipientError>,
+}
+/// request to send messages in one call, for clients that can't stream
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendBatchRequest {
+    /// at most dispatch.max_batch_size requests
+    #[prost(message, repeated, tag = "1")]
+    pub requests: ::prost::alloc::vec::Vec<SendRequest>,
+}
+/// result of a batch send, in request order
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendBatchResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: ::prost::alloc::vec::Vec<SendResult>,
+}
+/// result of a request of a batch
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendResult {
+    #[prost(oneof = "send_result::Result", tags = "1, 2")]
+    pub result: ::core::option::Option<send_result::Result>,
+}
+/// Nested message and enum types in `SendResult`.
+pub mod send_result {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Result {
+        #[prost(message, tag = "1")]
+        Response(super::SendResponse),
+        /// why the request is rejected
+        #[prost(message, tag = "2")]
+        Error(super::SendError),
+    }
+}
+/// error of a request of a batch, as the status the streaming send would have returned
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendError {
+    /// gRPC status code, e.g. 3 for INVALID_ARGUMENT
+    #[prost(int32, tag = "1")]
+    pub code: i32,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+}
+/// a recipient dropped from a message
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RecipientError {
+    /// the recipient as given in the request
+    #[prost(string, tag = "1")]
+    pub recipient: ::prost::alloc::string::String,
+    /// why the recipient is invalid
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// a delivery status change of a message
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StatusEvent {
+    /// unique identifier of the message
+    #[prost(string, tag = "1")]
+    pub message_id: ::prost::alloc::string::String,
+    /// status the message moved to
+    #[prost(enumeration = "DeliveryStatus", tag = "2")]
+    pub status: i32,
+    /// timestamp of when the status changed
+    #[prost(message, optional, tag = "3")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// optional detail of the change, e.g. the reason of a failure
+    #[prost(string, tag = "4")]
+    pub detail: ::prost::alloc::string::String,
+}
+/// request to get the delivery status of a message
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStatusRequest {
+    /// unique identifier of the message
+    #[prost(string, tag = "1")]
+    pub message_id: ::prost::alloc::string::String,
+}
+/// delivery status of a message
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStatusResponse {
+    /// unique identifier of the message
+    #[prost(string, tag = "1")]
+    pub message_id: ::prost::alloc::string::String,
+    /// latest status of the message
+    #[prost(enumeration = "DeliveryStatus", tag = "2")]
+    pub status: i32,
+    /// all status changes of the message, oldest first
+    #[prost(message, repeated, tag = "3")]
+    pub events: ::prost::alloc::vec::Vec<StatusEvent>,
+}
+/// request to watch delivery status changes
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchStatusRequest {
+    /// messages to watch, empty to watch all messages
+    #[prost(string, repeated, tag = "1")]
+    pub message_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// a bounce or a complaint about a message sent to a recipient
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bounce {
+    /// unique identifier of the bounced message, empty if unknown
+    #[prost(string, tag = "1")]
+    pub message_id: ::prost::alloc::string::String,
+    /// email address or phone number the message bounced from
+    #[prost(string, tag = "2")]
+    pub recipient: ::prost::alloc::string::String,
+    #[prost(enumeration = "BounceKind", tag = "3")]
+    pub kind: i32,
+    /// optional detail of the bounce, e.g. the DSN status and diagnostic code
+    #[prost(string, tag = "4")]
+    pub detail: ::prost::alloc::string::String,
+}
+/// request to report bounces, either already parsed or as a raw DSN email
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReportBounceRequest {
+    #[prost(oneof = "report_bounce_request::Report", tags = "1, 2")]
+    pub report: ::core::option::Option<report_bounce_request::Report>,
+}
+/// Nested message and enum types in `ReportBounceRequest`.
+pub mod report_bounce_request {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Report {
+        #[prost(message, tag = "1")]
+        Bounce(super::Bounce),
+        /// delivery status notification (RFC 3464) as received by the bounce mailbox
+        #[prost(bytes, tag = "2")]
+        Dsn(::prost::alloc::vec::Vec<u8>),
+    }
+}
+/// result of a bounce report
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReportBounceResponse {
+    /// bounces found in the report
+    #[prost(message, repeated, tag = "1")]
+    pub bounces: ::prost::alloc::vec::Vec<Bounce>,
+    /// recipients added to the suppression list
+    #[prost(string, repeated, tag = "2")]
+    pub suppressed: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// request to get the engagement of a campaign
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetCampaignStatsRequest {
+    #[prost(string, tag = "1")]
+    pub campaign: ::prost::alloc::string::String,
+}
+/// engagement of the tracked emails of a campaign. The stats are kept in memory only, so
+/// they start over when the service restarts
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CampaignStats {
+    #[prost(string, tag = "1")]
+    pub campaign: ::prost::alloc::string::String,
+    /// tracked emails delivered
+    #[prost(uint64, tag = "2")]
+    pub sent: u64,
+    /// emails opened at least once
+    #[prost(uint64, tag = "3")]
+    pub opened: u64,
+    /// emails with a link clicked at least once
+    #[prost(uint64, tag = "4")]
+    pub clicked: u64,
+    /// all the opens, including the repeated ones
+    #[prost(uint64, tag = "5")]
+    pub opens: u64,
+    /// all the clicks, including the repeated ones
+    #[prost(uint64, tag = "6")]
+    pub clicks: u64,
+    /// when the service started counting, the engagement before it is not included
+    #[prost(message, optional, tag = "7")]
+    pub since: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// priority of a message, higher priority lanes get a larger share of the workers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Priority {
+    Unspecified = 0,
+    /// bulk traffic, e.g. campaigns
+    Low = 1,
+    Normal = 2,
+    /// transactional traffic, e.g. password resets
+    High = 3,
+}
+impl Priority {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Priority::Unspecified => "PRIORITY_UNSPECIFIED",
+            Priority::Low => "PRIORITY_LOW",
+            Priority::Normal => "PRIORITY_NORMAL",
+            Priority::High => "PRIORITY_HIGH",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "PRIORITY_UNSPECIFIED" => Some(Self::Unspecified),
+            "PRIORITY_LOW" => Some(Self::Low),
+            "PRIORITY_NORMAL" => Some(Self::Normal),
+            "PRIORITY_HIGH" => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+/// delivery lifecycle of a message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DeliveryStatus {
+    Unspecified = 0,
+    /// message is accepted and queued for delivery
+    Accepted = 1,
+    /// message is handed over to the recipient
+    Delivered = 2,
+    /// message is rejected by the recipient side
+    Bounced = 3,
+    /// message could not be delivered
+    Failed = 4,
+    /// message is opened by the recipient
+    Opened = 5,
+    /// a link in the message is clicked by the recipient
+    Clicked = 6,
+    /// message is dropped as it's not delivered before it expires
+    Expired = 7,
+    /// message is reported as spam by the recipient
+    Complained = 8,
+}
+impl DeliveryStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Unspecified => "DELIVERY_STATUS_UNSPECIFIED",
+            DeliveryStatus::Accepted => "DELIVERY_STATUS_ACCEPTED",
+            DeliveryStatus::Delivered => "DELIVERY_STATUS_DELIVERED",
+            DeliveryStatus::Bounced => "DELIVERY_STATUS_BOUNCED",
+            DeliveryStatus::Failed => "DELIVERY_STATUS_FAILED",
+            DeliveryStatus::Opened => "DELIVERY_STATUS_OPENED",
+            DeliveryStatus::Clicked => "DELIVERY_STATUS_CLICKED",
+            DeliveryStatus::Expired => "DELIVERY_STATUS_EXPIRED",
+            DeliveryStatus::Complained => "DELIVERY_STATUS_COMPLAINED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DELIVERY_STATUS_UNSPECIFIED" => Some(Self::Unspecified),
+            "DELIVERY_STATUS_ACCEPTED" => Some(Self::Accepted),
+            "DELIVERY_STATUS_DELIVERED" => Some(Self::Delivered),
+            "DELIVERY_STATUS_BOUNCED" => Some(Self::Bounced),
+            "DELIVERY_STATUS_FAILED" => Some(Self::Failed),
+            "DELIVERY_STATUS_OPENED" => Some(Self::Opened),
+            "DELIVERY_STATUS_CLICKED" => Some(Self::Clicked),
+            "DELIVERY_STATUS_EXPIRED" => Some(Self::Expired),
+            "DELIVERY_STATUS_COMPLAINED" => Some(Self::Complained),
+            _ => None,
+        }
+    }
+}
+/// kind of a bounce notification
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum BounceKind {
+    Unspecified = 0,
+    /// permanent failure, e.g. the mailbox doesn't exist
+    Hard = 1,
+    /// temporary failure, e.g. the mailbox is full
+    Soft = 2,
+    /// recipient reported the message as spam
+    Complaint = 3,
+}
+impl BounceKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            BounceKind::Unspecified => "BOUNCE_KIND_UNSPECIFIED",
+            BounceKind::Hard => "BOUNCE_KIND_HARD",
+            BounceKind::Soft => "BOUNCE_KIND_SOFT",
+            BounceKind::Complaint => "BOUNCE_KIND_COMPLAINT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "BOUNCE_KIND_UNSPECIFIED" => Some(Self::Unspecified),
+            "BOUNCE_KIND_HARD" => Some(Self::Hard),
+            "BOUNCE_KIND_SOFT" => Some(Self::Soft),
+            "BOUNCE_KIND_COMPLAINT" => Some(Self::Complaint),
+            _ => None,
+        }
+    }
 }
 /// Generated client implementations.
 pub mod notification_client {
@@ -190,6 +556,100 @@ pub mod notification_client {
                 .insert(GrpcMethod::new("notification.Notification", "Send"));
             self.inner.streaming(req, path, codec).await
         }
+        pub async fn send_batch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SendBatchRequest>,
+        ) -> std::result::Result<tonic::Response<super::SendBatchResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/notification.Notification/SendBatch");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("notification.Notification", "SendBatch"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::GetStatusResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/notification.Notification/GetStatus");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("notification.Notification", "GetStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn watch_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::StatusEvent>>,
+            tonic::Status,
+        > {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/notification.Notification/WatchStatus");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("notification.Notification", "WatchStatus"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn report_bounce(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReportBounceRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReportBounceResponse>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/notification.Notification/ReportBounce");
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("notification.Notification", "ReportBounce"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_campaign_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetCampaignStatsRequest>,
+        ) -> std::result::Result<tonic::Response<super::CampaignStats>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/notification.Notification/GetCampaignStats");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new(
+                "notification.Notification",
+                "GetCampaignStats",
+            ));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -208,6 +668,31 @@ pub mod notification_server {
             &self,
             request: tonic::Request<tonic::Streaming<super::SendRequest>>,
         ) -> std::result::Result<tonic::Response<Self::SendStream>, tonic::Status>;
+        async fn send_batch(
+            &self,
+            request: tonic::Request<super::SendBatchRequest>,
+        ) -> std::result::Result<tonic::Response<super::SendBatchResponse>, tonic::Status>;
+        async fn get_status(
+            &self,
+            request: tonic::Request<super::GetStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::GetStatusResponse>, tonic::Status>;
+        /// Server streaming response type for the WatchStatus method.
+        type WatchStatusStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::StatusEvent, tonic::Status>,
+            > + Send
+            + 'static;
+        async fn watch_status(
+            &self,
+            request: tonic::Request<super::WatchStatusRequest>,
+        ) -> std::result::Result<tonic::Response<Self::WatchStatusStream>, tonic::Status>;
+        async fn report_bounce(
+            &self,
+            request: tonic::Request<super::ReportBounceRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReportBounceResponse>, tonic::Status>;
+        async fn get_campaign_stats(
+            &self,
+            request: tonic::Request<super::GetCampaignStatsRequest>,
+        ) -> std::result::Result<tonic::Response<super::CampaignStats>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct NotificationServer<T: Notification> {
@@ -326,6 +811,216 @@ pub mod notification_server {
                     };
                     Box::pin(fut)
                 }
+                "/notification.Notification/SendBatch" => {
+                    #[allow(non_camel_case_types)]
+                    struct SendBatchSvc<T: Notification>(pub Arc<T>);
+                    impl<T: Notification> tonic::server::UnaryService<super::SendBatchRequest> for SendBatchSvc<T> {
+                        type Response = super::SendBatchResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SendBatchRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Notification>::send_batch(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SendBatchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/notification.Notification/GetStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetStatusSvc<T: Notification>(pub Arc<T>);
+                    impl<T: Notification> tonic::server::UnaryService<super::GetStatusRequest> for GetStatusSvc<T> {
+                        type Response = super::GetStatusResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Notification>::get_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/notification.Notification/WatchStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchStatusSvc<T: Notification>(pub Arc<T>);
+                    impl<T: Notification>
+                        tonic::server::ServerStreamingService<super::WatchStatusRequest>
+                        for WatchStatusSvc<T>
+                    {
+                        type Response = super::StatusEvent;
+                        type ResponseStream = T::WatchStatusStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Notification>::watch_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = WatchStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/notification.Notification/ReportBounce" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReportBounceSvc<T: Notification>(pub Arc<T>);
+                    impl<T: Notification> tonic::server::UnaryService<super::ReportBounceRequest>
+                        for ReportBounceSvc<T>
+                    {
+                        type Response = super::ReportBounceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReportBounceRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Notification>::report_bounce(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReportBounceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/notification.Notification/GetCampaignStats" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetCampaignStatsSvc<T: Notification>(pub Arc<T>);
+                    impl<T: Notification>
+                        tonic::server::UnaryService<super::GetCampaignStatsRequest>
+                        for GetCampaignStatsSvc<T>
+                    {
+                        type Response = super::CampaignStats;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetCampaignStatsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Notification>::get_campaign_stats(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetCampaignStatsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
@@ -1,30 +1,40 @@
-use std::{net::SocketAddr, time::Duration};
+use std::{fs, net::SocketAddr, path::PathBuf, time::Duration};
 
 use anyhow::Result;
 use crm_send::{
     pb::{
-        notification_client::NotificationClient, EmailMessage, InAppMessage, SendRequest,
-        SmsMessage,
+        notification_client::NotificationClient, Attachment, DeliveryStatus, EmailMessage,
+        GetStatusRequest, InAppMessage, SendRequest, SmsMessage,
     },
-    AppConfig, NotificationService,
+    AppConfig, EmailFormat, NotificationService, SinkConfig,
 };
 use futures::StreamExt;
-use tokio::time::sleep;
-use tonic::{transport::Server, Request};
+use mail_parser::{MessageParser, MimeHeaders};
+use tokio::{net::TcpListener, time::sleep};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    transport::{Channel, Server},
+    Request,
+};
+use uuid::Uuid;
 
 #[tokio::test]
 async fn test_send_integration_test() -> Result<()> {
-    let addr = start_server().await?;
+    let config = AppConfig::load_for_test()?;
+    let addr = start_server(config).await?;
     let mut client = NotificationClient::connect(format!("http://{}", addr)).await?;
     let stream = tokio_stream::iter(vec![
         SendRequest {
             msg: Some(EmailMessage::fake().into()),
+            ..Default::default()
         },
         SendRequest {
             msg: Some(SmsMessage::fake().into()),
+            ..Default::default()
         },
         SendRequest {
             msg: Some(InAppMessage::fake().into()),
+            ..Default::default()
         },
     ]);
 
@@ -37,21 +47,141 @@ async fn test_send_integration_test() -> Result<()> {
     Ok(())
 }
 
-async fn start_server() -> Result<SocketAddr> {
-    let config = AppConfig::load()?;
-    let addr = format!("[::1]:{}", config.server.port).parse()?;
+#[tokio::test]
+async fn file_sink_should_write_messages() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("crm-send-{}", Uuid::new_v4()));
+    let mut config = AppConfig::load_for_test()?;
+    config.sink = SinkConfig::File {
+        dir: dir.clone(),
+        email_format: EmailFormat::Eml,
+    };
+    let addr = start_server(config).await?;
+    let mut client = NotificationClient::connect(format!("http://{}", addr)).await?;
+
+    let mut email = EmailMessage::fake();
+    email.html_body = "<p>Hello, world!</p>".to_string();
+    email.attachments = vec![Attachment {
+        filename: "report.csv".to_string(),
+        content_type: "text/csv".to_string(),
+        content: b"id,name\n1,acme\n".to_vec(),
+        ..Default::default()
+    }];
+    let sms = SmsMessage::fake();
+    let in_app = InAppMessage::fake();
+    let ids = [
+        email.message_id.clone(),
+        sms.message_id.clone(),
+        in_app.message_id.clone(),
+    ];
+    let stream = tokio_stream::iter(vec![
+        SendRequest::from(email.clone()),
+        SendRequest::from(sms.clone()),
+        SendRequest::from(in_app.clone()),
+    ]);
+    let response = client.send(Request::new(stream)).await?.into_inner();
+    let ret: Vec<_> = response.collect().await;
+    assert_eq!(ret.len(), 3);
+    for id in ids {
+        wait_for_delivery(&mut client, &id).await?;
+    }
+
+    let eml = fs::read(dir.join("email").join(format!("{}.eml", email.message_id)))?;
+    let parsed = MessageParser::default().parse(&eml).unwrap();
+    assert_eq!(parsed.subject(), Some("Hello"));
+    assert_eq!(
+        parsed.to().unwrap().first().unwrap().address(),
+        Some(email.recipients[0].as_str())
+    );
+    assert!(parsed.body_html(0).unwrap().contains("Hello, world!"));
+    assert_eq!(
+        parsed.attachment(0).unwrap().attachment_name(),
+        Some("report.csv")
+    );
+
+    let line = read_jsonl(dir.join("sms.jsonl"))?.remove(0);
+    assert_eq!(line["message_id"], sms.message_id);
+    assert_eq!(line["recipients"][0], sms.recipients[0]);
+    assert_eq!(line["body"], sms.body);
+    assert!(line["sent_at"].is_string());
+    let line = read_jsonl(dir.join("in_app.jsonl"))?.remove(0);
+    assert_eq!(line["device_id"], in_app.device_id);
+    assert_eq!(line["title"], in_app.title);
 
-    let svc = NotificationService::new(config).into_server();
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn file_sink_should_append_emails_to_mbox() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("crm-send-{}", Uuid::new_v4()));
+    let mut config = AppConfig::load_for_test()?;
+    config.sink = SinkConfig::File {
+        dir: dir.clone(),
+        email_format: EmailFormat::Mbox,
+    };
+    let addr = start_server(config).await?;
+    let mut client = NotificationClient::connect(format!("http://{}", addr)).await?;
+
+    let emails: Vec<_> = (0..3).map(|_| EmailMessage::fake()).collect();
+    let stream = tokio_stream::iter(emails.clone().into_iter().map(SendRequest::from));
+    let response = client.send(Request::new(stream)).await?.into_inner();
+    let ret: Vec<_> = response.collect().await;
+    assert_eq!(ret.len(), 3);
+    for email in emails.iter() {
+        wait_for_delivery(&mut client, &email.message_id).await?;
+    }
+
+    let mbox = fs::read_to_string(dir.join("email.mbox"))?;
+    let messages: Vec<_> = mbox
+        .split("\n\nFrom ")
+        .map(|m| m.trim_start_matches("From "))
+        .collect();
+    assert_eq!(messages.len(), 3);
+    for email in emails.iter() {
+        let id = format!("<{}@crm-send>", email.message_id);
+        assert_eq!(messages.iter().filter(|m| m.contains(&id)).count(), 1);
+    }
+
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+async fn wait_for_delivery(client: &mut NotificationClient<Channel>, id: &str) -> Result<()> {
+    for _ in 0..50 {
+        let req = GetStatusRequest {
+            message_id: id.to_string(),
+        };
+        let res = client.get_status(req).await?.into_inner();
+        if res.status == DeliveryStatus::Delivered as i32 {
+            return Ok(());
+        }
+        sleep(Duration::from_millis(20)).await;
+    }
+    anyhow::bail!("Message {} is not delivered", id)
+}
+
+fn read_jsonl(path: PathBuf) -> Result<Vec<serde_json::Value>> {
+    let lines = fs::read_to_string(path)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    Ok(lines)
+}
+
+async fn start_server(config: AppConfig) -> Result<SocketAddr> {
+    // a free port of the os, so the tests could run in parallel
+    let listener = TcpListener::bind("[::1]:0").await?;
+    let addr = listener.local_addr()?;
+
+    let svc = NotificationService::new(config)?.into_server();
 
     tokio::spawn(async move {
         Server::builder()
             .add_service(svc)
-            .serve(addr)
+            .serve_with_incoming(TcpListenerStream::new(listener))
             .await
             .unwrap();
     });
 
-    sleep(Duration::from_micros(1)).await;
-
     Ok(addr)
 }
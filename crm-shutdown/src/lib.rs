@@ -0,0 +1,115 @@
+use std::{future::Future, pin::Pin, time::Duration};
+use tokio::{
+    signal,
+    sync::oneshot,
+    time::{timeout_at, Instant},
+};
+use tracing::{info, warn};
+
+/// resolves when the server should stop accepting requests, pass it to `serve_with_shutdown`
+pub type Stop = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// run the server until it exits, or until ctrl-c or SIGTERM. On a signal, stop accepting
+/// requests and wait up to `timeout` for the in-flight ones. Returns the shutdown deadline,
+/// or None if the server exited by itself
+pub async fn serve<F, E>(
+    server: impl FnOnce(Stop) -> F,
+    timeout: Duration,
+) -> Result<Option<Instant>, E>
+where
+    F: Future<Output = Result<(), E>>,
+{
+    serve_until(server, shutdown_signal(), timeout).await
+}
+
+/// same as `serve`, but shut down when `signal` resolves
+pub async fn serve_until<F, E>(
+    server: impl FnOnce(Stop) -> F,
+    signal: impl Future<Output = ()>,
+    timeout: Duration,
+) -> Result<Option<Instant>, E>
+where
+    F: Future<Output = Result<(), E>>,
+{
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = server(Box::pin(async {
+        stopped.await.ok();
+    }));
+    tokio::pin!(server);
+    tokio::select! {
+        ret = &mut server => return ret.map(|_| None),
+        _ = signal => {}
+    }
+
+    info!("Shutting down, waiting up to {:?}", timeout);
+    let deadline = Instant::now() + timeout;
+    let _ = stop.send(());
+    match timeout_at(deadline, server).await {
+        Ok(ret) => ret?,
+        Err(_) => warn!("In-flight requests not finished before the deadline, abort them"),
+    }
+    Ok(Some(deadline))
+}
+
+/// resolves on ctrl-c, or SIGTERM on unix
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c().await.expect("Failed to listen for ctrl-c");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future;
+
+    #[tokio::test]
+    async fn serve_should_return_none_when_server_exits() {
+        let ret = serve_until(
+            |_stop| async { Ok::<_, ()>(()) },
+            future::pending(),
+            Duration::from_secs(1),
+        )
+        .await;
+        assert_eq!(ret, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn serve_should_stop_server_on_signal() {
+        let ret = serve_until(
+            |stop| async {
+                stop.await;
+                Ok::<_, ()>(())
+            },
+            future::ready(()),
+            Duration::from_secs(10),
+        )
+        .await;
+        assert!(ret.unwrap().unwrap() > Instant::now());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn serve_should_give_up_on_server_after_timeout() {
+        let ret = serve_until(
+            |_stop| future::pending::<Result<(), ()>>(),
+            future::ready(()),
+            Duration::from_secs(10),
+        )
+        .await;
+        assert!(ret.unwrap().unwrap() <= Instant::now());
+    }
+}
@@ -8,14 +8,14 @@ use crate::{
     CrmService,
 };
 use chrono::{Duration, Utc};
-use crm_metadata::pb::{Content, MaterializeRequest};
+use crm_metadata::pb::{batch_get_item::Item, BatchGetRequest, Content, TrendingRequest};
 use crm_send::pb::SendRequest;
 use futures::StreamExt;
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::{sync::Arc, time::Duration as StdDuration};
+use tokio::{sync::mpsc, time::timeout};
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Response, Status, Streaming};
-use tracing::warn;
+use tracing::{info, warn};
 use user_stat::pb::{QueryRequest, User};
 
 const CHANNEL_SIZE: usize = 1024;
@@ -32,12 +32,15 @@ impl CrmService {
                 Duration::days(1),
             )
             .await?;
-        let contents = self.get_contents(request.content_ids).await?;
+        let contents = self
+            .get_contents(request.content_ids, &request.locale)
+            .await?;
         // 创建发送者和接收者channel
         let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
 
         // 获取发送者邮箱
         let sender = self.config.server.sender_email.clone();
+        let campaign = request.id.clone();
         // 在单独的task中处理用户统计信息
         tokio::spawn(async move {
             while let Some(Ok(user)) = res_user_stats.next().await {
@@ -47,7 +50,13 @@ impl CrmService {
                 let tx = tx.clone();
 
                 // 构造发送请求
-                let req = SendRequest::new("Welcome".to_string(), sender, &[user.email], &contents);
+                let req = SendRequest::new(
+                    campaign.clone(),
+                    "Welcome".to_string(),
+                    sender,
+                    &[user.email],
+                    &contents,
+                );
                 // 发送请求
                 if let Err(e) = tx.send(req).await {
                     warn!("Failed to send message: {:?}", e);
@@ -57,7 +66,7 @@ impl CrmService {
         // 将发送请求转换为ReceiverStream
         let reqs = ReceiverStream::new(rx);
 
-        self.notification.clone().send(reqs).await?;
+        self.send_all(reqs).await?;
 
         Ok(Response::new(WelcomeResponse { id: request.id }))
     }
@@ -70,10 +79,16 @@ impl CrmService {
                 Duration::days(1),
             )
             .await?;
-        let contents = self.get_contents(request.content_ids).await?;
+        let contents = if request.content_ids.is_empty() {
+            self.get_trending(&request.locale).await?
+        } else {
+            self.get_contents(request.content_ids, &request.locale)
+                .await?
+        };
         let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
 
         let sender = self.config.server.sender_email.clone();
+        let campaign = request.id.clone();
 
         tokio::spawn(async move {
             while let Some(Ok(user)) = res_user_stats.next().await {
@@ -81,7 +96,13 @@ impl CrmService {
                 let sender = sender.clone();
                 let tx = tx.clone();
 
-                let req = SendRequest::new("Recall".to_string(), sender, &[user.email], &contents);
+                let req = SendRequest::new(
+                    campaign.clone(),
+                    "Recall".to_string(),
+                    sender,
+                    &[user.email],
+                    &contents,
+                );
                 if let Err(e) = tx.send(req).await {
                     warn!("Failed to send message: {:?}", e);
                 }
@@ -90,7 +111,7 @@ impl CrmService {
 
         let reqs = ReceiverStream::new(rx);
 
-        self.notification.clone().send(reqs).await?;
+        self.send_all(reqs).await?;
         Ok(Response::new(RecallResponse { id: request.id }))
     }
 
@@ -105,6 +126,7 @@ impl CrmService {
         let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
 
         let sender = self.config.server.sender_email.clone();
+        let campaign = request.id.clone();
 
         tokio::spawn(async move {
             while let Some(Ok(user)) = res_user_stats.next().await {
@@ -113,6 +135,7 @@ impl CrmService {
 
                 println!("Remind: {:?}", user.name);
                 let req = SendRequest::new_remind(
+                    campaign.clone(),
                     "Remind".to_string(),
                     sender,
                     &[user.email],
@@ -128,10 +151,46 @@ impl CrmService {
 
         let reqs = ReceiverStream::new(rx);
 
-        self.notification.clone().send(reqs).await?;
+        self.send_all(reqs).await?;
         Ok(Response::new(RemindResponse { id: request.id }))
     }
 
+    /// send the requests and wait for all their acks, as the notification service stops
+    /// sending once the ack stream is dropped. Fails if any message failed, after the rest
+    /// are sent, or if they're not all sent before the send timeout
+    async fn send_all(&self, reqs: ReceiverStream<SendRequest>) -> Result<(), Status> {
+        let send_timeout = StdDuration::from_secs(self.config.server.send_timeout);
+        let mut acks = self.notification.clone().send(reqs).await?.into_inner();
+        let (mut sent, mut failed) = (0, 0);
+        let drained = timeout(send_timeout, async {
+            while let Some(ack) = acks.next().await {
+                match ack {
+                    Ok(_) => sent += 1,
+                    Err(e) => {
+                        warn!("Failed to send message: {:?}", e);
+                        failed += 1;
+                    }
+                }
+            }
+        })
+        .await;
+        info!("{} messages sent, {} failed", sent, failed);
+        if drained.is_err() {
+            return Err(Status::deadline_exceeded(format!(
+                "Messages not all sent in {:?}, {} sent and {} failed so far",
+                send_timeout, sent, failed
+            )));
+        }
+        if failed > 0 {
+            return Err(Status::internal(format!(
+                "{} of {} messages failed to send",
+                failed,
+                sent + failed
+            )));
+        }
+        Ok(())
+    }
+
     async fn query_user_stats(
         &self,
         field: &str,
@@ -144,16 +203,60 @@ impl CrmService {
         Ok(self.user_stats.clone().query(query).await?.into_inner())
     }
 
-    async fn get_contents(&self, content_ids: Vec<u32>) -> Result<Arc<Vec<Content>>, Status> {
-        let contents = self
+    /// the contents available now, the unavailable and missing ones are dropped
+    async fn get_contents(
+        &self,
+        content_ids: Vec<u32>,
+        locale: &str,
+    ) -> Result<Arc<Vec<Content>>, Status> {
+        let req = BatchGetRequest {
+            ids: content_ids,
+            locale: locale.to_string(),
+            only_available: true,
+        };
+        let items = self
             .metadata
             .clone()
-            .materialize(MaterializeRequest::new_with_ids(&content_ids))
+            .batch_get(req)
             .await?
             .into_inner()
-            .filter_map(|v| async move { v.ok() })
-            .collect()
-            .await;
+            .items;
+        let contents = items
+            .into_iter()
+            .filter_map(|item| match item.item {
+                Some(Item::Content(content)) => Some(content),
+                Some(Item::Missing(reason)) => {
+                    warn!("Skip content {}: {}", item.id, reason);
+                    None
+                }
+                None => None,
+            })
+            .collect();
         Ok(Arc::new(contents))
     }
+
+    /// the trending contents, only the available ones are ranked by the metadata service.
+    /// Fails if there is none, rather than sending emails without contents
+    async fn get_trending(&self, locale: &str) -> Result<Arc<Vec<Content>>, Status> {
+        let contents = self
+            .metadata
+            .clone()
+            .trending(TrendingRequest::default())
+            .await?
+            .into_inner()
+            .contents;
+        let contents = if locale.is_empty() {
+            Arc::new(contents)
+        } else {
+            // trending contents come in the default locale, get them again in the given one
+            let ids = contents.iter().map(|c| c.id).collect();
+            self.get_contents(ids, locale).await?
+        };
+        if contents.is_empty() {
+            return Err(Status::failed_precondition(
+                "No trending contents to send, give the content_ids instead",
+            ));
+        }
+        Ok(contents)
+    }
 }
@@ -25,6 +25,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let token = include_str!("../../fixtures/token").trim();
     let token: MetadataValue<_> = format!("Bearer {}", token).parse()?;
 
+    // the interceptor returns a Status as tonic requires
+    #[allow(clippy::result_large_err)]
     let mut client = CrmClient::with_interceptor(channel, move |mut req: Request<()>| {
         req.metadata_mut().insert("authorization", token.clone());
         Ok(req)
@@ -17,6 +17,12 @@ pub struct ServerConfig {
     pub user_stats: String,
     pub notification: String,
     pub tls: Option<TlsConfig>,
+    /// how long (in seconds) to drain the in-flight requests on shutdown
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
+    /// how long (in seconds) a campaign waits for the notification service to send it
+    #[serde(default = "default_send_timeout")]
+    pub send_timeout: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -56,3 +62,11 @@ impl AppConfig {
         bail!("Failed to load configuration");
     }
 }
+
+fn default_shutdown_timeout() -> u64 {
+    30
+}
+
+fn default_send_timeout() -> u64 {
+    600
+}
@@ -4,6 +4,7 @@
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WelcomeRequest {
+    /// id of the campaign, the opens and clicks of the emails are counted by
     #[prost(string, tag = "1")]
     pub id: ::prost::alloc::string::String,
     /// interval for registered time (say 7 is registered 7 days ago)
@@ -12,6 +13,9 @@ pub struct WelcomeRequest {
     #[prost(uint32, repeated, tag = "3")]
     #[builder(setter(each(name = "content_id", into)))]
     pub content_ids: ::prost::alloc::vec::Vec<u32>,
+    /// the locale of the content names, the default ones if empty
+    #[prost(string, tag = "4")]
+    pub locale: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -24,12 +28,17 @@ pub struct WelcomeResponse {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RecallRequest {
+    /// id of the campaign, the opens and clicks of the emails are counted by
     #[prost(string, tag = "1")]
     pub id: ::prost::alloc::string::String,
     #[prost(uint32, tag = "2")]
     pub last_visit_interval: u32,
+    /// the trending contents if empty, fails with FAILED_PRECONDITION if there are none
     #[prost(uint32, repeated, tag = "3")]
     pub content_ids: ::prost::alloc::vec::Vec<u32>,
+    /// the locale of the content names, the default ones if empty
+    #[prost(string, tag = "4")]
+    pub locale: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -42,6 +51,7 @@ pub struct RecallResponse {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RemindRequest {
+    /// id of the campaign, the opens and clicks of the emails are counted by
     #[prost(string, tag = "1")]
     pub id: ::prost::alloc::string::String,
     #[prost(uint32, tag = "2")]
@@ -1,6 +1,7 @@
+use std::{mem, time::Duration};
+
 use anyhow::Result;
 use crm::{AppConfig, CrmService};
-use std::mem;
 use tonic::transport::{Identity, Server, ServerTlsConfig};
 use tracing::{info, level_filters::LevelFilter};
 use tracing_subscriber::{fmt::Layer, layer::SubscriberExt, util::SubscriberInitExt, Layer as _};
@@ -15,21 +16,24 @@ async fn main() -> Result<()> {
 
     let addr = config.server.port;
     let addr = format!("[::1]:{}", addr).parse().unwrap();
+    let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout);
     info!("CRM service listening on {}", addr);
     let svc = CrmService::try_new(config).await?.into_server()?;
 
+    let mut builder = Server::builder();
     if let Some(tls) = tls {
         let identity = Identity::from_pem(tls.cert, tls.key);
         info!("TLS enabled");
-        Server::builder()
-            .tls_config(ServerTlsConfig::new().identity(identity))?
-            .add_service(svc)
-            .serve(addr)
-            .await?;
+        builder = builder.tls_config(ServerTlsConfig::new().identity(identity))?;
     } else {
         info!("TLS disabled");
-        Server::builder().add_service(svc).serve(addr).await?;
     }
 
+    // on shutdown, stop accepting requests, and let the in-flight campaigns finish sending
+    crm_shutdown::serve(
+        |stop| builder.add_service(svc).serve_with_shutdown(addr, stop),
+        shutdown_timeout,
+    )
+    .await?;
     Ok(())
 }
@@ -103,7 +103,7 @@ fn list_to_string(list: Vec<i32>) -> String {
 async fn raw_insert1(users: HashSet<UserStat>, pool: &PgPool) -> Result<(), sqlx::Error> {
     let batch_size = 1000; // 每次批量插入的大小
     let users: Vec<UserStat> = users.into_iter().collect();
-    let total_batches = (users.len() + batch_size - 1) / batch_size; // 计算总批次数
+    let total_batches = users.len().div_ceil(batch_size); // 计算总批次数
 
     let mut tasks = Vec::new();
 
@@ -35,21 +35,19 @@ impl UserStatsService {
             .map(|row| {
                 let email: String = row
                     .try_get("email")
-                    .map_err(|e| Status::internal(format!("Failed to get email: {}", e)))?;
+                    .map_err(|e| format!("Failed to get email: {}", e))?;
 
                 let name: String = row
                     .try_get("name")
-                    .map_err(|e| Status::internal(format!("Failed to get name: {}", e)))?;
+                    .map_err(|e| format!("Failed to get name: {}", e))?;
 
-                let viewed_but_not_started: Vec<i32> =
-                    row.try_get("viewed_but_not_started").map_err(|e| {
-                        Status::internal(format!("Failed to get viewed_but_not_started: {}", e))
-                    })?;
+                let viewed_but_not_started: Vec<i32> = row
+                    .try_get("viewed_but_not_started")
+                    .map_err(|e| format!("Failed to get viewed_but_not_started: {}", e))?;
 
-                let started_but_not_finished: Vec<i32> =
-                    row.try_get("started_but_not_finished").map_err(|e| {
-                        Status::internal(format!("Failed to get started_but_not_finished: {}", e))
-                    })?;
+                let started_but_not_finished: Vec<i32> = row
+                    .try_get("started_but_not_finished")
+                    .map_err(|e| format!("Failed to get started_but_not_finished: {}", e))?;
 
                 Ok(User {
                     email,
@@ -64,7 +62,8 @@ impl UserStatsService {
                         .collect(),
                 })
             })
-            .collect::<Result<Vec<User>, Status>>()?;
+            .collect::<Result<Vec<User>, String>>()
+            .map_err(Status::internal)?;
 
         Ok(Response::new(Box::pin(futures::stream::iter(
             ret.into_iter().map(Ok),
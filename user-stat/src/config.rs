@@ -17,6 +17,9 @@ pub struct AuthConfig {
 pub struct ServerConfig {
     pub port: u16,
     pub db_url: String,
+    /// how long (in seconds) to drain the in-flight requests on shutdown
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
 }
 
 impl AppConfig {
@@ -50,6 +53,10 @@ impl AppConfig {
     }
 }
 
+fn default_shutdown_timeout() -> u64 {
+    30
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
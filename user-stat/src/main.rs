@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use tonic::transport::Server;
 use tracing::{info, level_filters::LevelFilter};
@@ -12,9 +14,19 @@ async fn main() -> Result<()> {
     let config = AppConfig::load().expect("Failed to load config");
     let addr = config.server.port;
     let addr = format!("[::1]:{}", addr).parse().unwrap();
+    let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout);
     info!("User stats service listening on {}", addr);
     let service = UserStatsService::new(config).await.into_server();
 
-    Server::builder().add_service(service).serve(addr).await?;
+    // on shutdown, stop accepting requests, and let the in-flight streams finish
+    crm_shutdown::serve(
+        |stop| {
+            Server::builder()
+                .add_service(service)
+                .serve_with_shutdown(addr, stop)
+        },
+        shutdown_timeout,
+    )
+    .await?;
     Ok(())
 }